@@ -47,17 +47,13 @@ impl Serializable for Contract {
 
 impl Deserializable for Contract {
     fn try_deserialize(bytes: &Bytes) -> Result<(Self, &[u8]), DeserErr> {
-        let (rlp_code, rest1) =
-            RlpItem::try_deserialize(bytes).map_err(|_| DeserErr::BadRlpItem)?;
-        let (rlp_symbols, rest2) =
-            RlpItem::try_deserialize(rest1).map_err(|_| DeserErr::BadRlpItem)?;
-        let rlp_annotations = RlpItem::deserialize(rest2).map_err(|_| DeserErr::BadRlpItem)?;
-
-        let code_bytes = rlp_code.byte_array().map_err(|_| DeserErr::BadRlpItem)?;
-        let symbols_bytes = rlp_symbols.byte_array().map_err(|_| DeserErr::BadRlpItem)?;
-        let annotations_bytes = rlp_annotations
-            .byte_array()
-            .map_err(|_| DeserErr::BadRlpItem)?;
+        let (rlp_code, rest1) = RlpItem::try_deserialize(bytes)?;
+        let (rlp_symbols, rest2) = RlpItem::try_deserialize(rest1)?;
+        let rlp_annotations = RlpItem::deserialize(rest2)?;
+
+        let code_bytes = rlp_code.byte_array()?;
+        let symbols_bytes = rlp_symbols.byte_array()?;
+        let annotations_bytes = rlp_annotations.byte_array()?;
 
         let code = Vec::<Function>::deserialize(&code_bytes)?;
         let symbols = Symbols::deserialize(&symbols_bytes)?;
@@ -1,3 +1,5 @@
+use std::fmt;
+
 use aeser::{error, rlp, Bytes};
 
 use super::value::Value;
@@ -55,3 +57,96 @@ pub enum DeserErr {
     Failed,
     BadTypeSig,
 }
+
+impl fmt::Display for SerErr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = match self {
+            SerErr::NonEmptyStoreMapCache => "store map with a non-empty cache",
+            SerErr::InvalidVariantTag => "variant tag out of range of its arities",
+            SerErr::MapAsKeyType => "map used as a map key",
+            SerErr::HeteroMapKeys => "map keys of different types",
+            SerErr::HeteroMapValues => "map values of different types",
+            SerErr::ArityValuesMismatch => "variant arity does not match its values",
+            SerErr::TupleSizeLimitExceeded => "tuple type exceeds the size limit",
+            SerErr::VariantSizeLimitExceeded => "variant type exceeds the size limit",
+            SerErr::DuplicateFunctionName => "duplicate function name",
+        };
+        f.write_str(msg)
+    }
+}
+
+impl std::error::Error for SerErr {}
+
+impl fmt::Display for DeserErr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeserErr::Empty => f.write_str("empty input"),
+            DeserErr::InvalidIdByte(b) => write!(f, "invalid id type byte {b:#04x}"),
+            DeserErr::InvalidObjectByte(b) => write!(f, "invalid object type byte {b:#04x}"),
+            DeserErr::InvalidBytesObject => f.write_str("malformed bytes object"),
+            DeserErr::InvalidObject => f.write_str("malformed object"),
+            DeserErr::RlpErr(_) => f.write_str("malformed RLP"),
+            DeserErr::ExternalErr(_) => f.write_str("invalid RLP item"),
+            DeserErr::InvalidTypeVar => f.write_str("malformed type variable"),
+            DeserErr::InvalidTypeId(b) => write!(f, "invalid type id byte {b:#04x}"),
+            DeserErr::Trailing {
+                input,
+                undecoded,
+                decoded,
+            } => write!(
+                f,
+                "{} trailing bytes after {:?} ending at position {}",
+                undecoded.len(),
+                decoded,
+                input.len() - undecoded.len()
+            ),
+            DeserErr::InvalidIntValue => f.write_str("malformed integer"),
+            DeserErr::InvalidBytesType => f.write_str("malformed bytes type"),
+            DeserErr::BytesSizeTooBig => f.write_str("bytes size too big"),
+            DeserErr::InvalidTuple => f.write_str("malformed tuple"),
+            DeserErr::InvalidTupleOrVariant => f.write_str("malformed tuple or variant type"),
+            DeserErr::InvalidTypeObjectByte(b) => write!(f, "invalid type object byte {b:#04x}"),
+            DeserErr::InvalidString => f.write_str("malformed string"),
+            DeserErr::InvalidContractBytearray => f.write_str("malformed contract bytearray"),
+            DeserErr::InvalidListSize => f.write_str("malformed list size"),
+            DeserErr::InvalidTupleSize => f.write_str("malformed tuple size"),
+            DeserErr::InvalidMapSize => f.write_str("malformed map size"),
+            DeserErr::InvalidMapId => f.write_str("malformed map id"),
+            DeserErr::TooLargeTagInVariant => f.write_str("variant tag out of range of its arities"),
+            DeserErr::BadVariant => f.write_str("malformed variant"),
+            DeserErr::TagDoesNotMatchTypeInVariant => {
+                f.write_str("variant values do not match its arity")
+            }
+            DeserErr::CalldataDecodeErr => f.write_str("malformed calldata"),
+            DeserErr::BadSymbolsTable => f.write_str("malformed symbols table"),
+            DeserErr::BadString => f.write_str("malformed string"),
+            DeserErr::BadAttributes => f.write_str("malformed function attributes"),
+            DeserErr::BadRlpItem => f.write_str("unexpected RLP item"),
+            DeserErr::BadAnnotation => f.write_str("malformed annotation"),
+            DeserErr::Failed => f.write_str("decoding failed"),
+            DeserErr::BadTypeSig => f.write_str("malformed type signature"),
+        }
+    }
+}
+
+impl std::error::Error for DeserErr {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DeserErr::RlpErr(e) => Some(e),
+            DeserErr::ExternalErr(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<rlp::DecodingErr> for DeserErr {
+    fn from(e: rlp::DecodingErr) -> Self {
+        DeserErr::RlpErr(e)
+    }
+}
+
+impl From<error::DecodingErr> for DeserErr {
+    fn from(e: error::DecodingErr) -> Self {
+        DeserErr::ExternalErr(e)
+    }
+}
@@ -3,7 +3,7 @@ use std::collections::BTreeMap;
 
 use num_bigint::{BigInt, BigUint, Sign};
 
-use aeser::rlp::{FromRlpItem, RlpItem, ToRlpItem};
+use aeser::rlp::{FromRlpRef, RlpRef, ToRlpItem};
 use aeser::Bytes;
 use num_traits::{ToPrimitive, Zero};
 
@@ -453,7 +453,7 @@ fn serialize_address_object(address: &Bytes, object_id: u8) -> Bytes {
 }
 
 fn rlp_decode_bytes(bytes: &[u8]) -> Result<(Bytes, &[u8]), DeserErr> {
-    let (item, rest) = RlpItem::try_deserialize(bytes).map_err(|e| DeserErr::RlpErr(e))?;
-    let decoded = Vec::<u8>::from_rlp_item(&item).map_err(|e| DeserErr::ExternalErr(e))?;
+    let (item, rest) = RlpRef::try_deserialize(bytes)?;
+    let decoded = Vec::<u8>::from_rlp_ref(&item)?;
     Ok((decoded, rest))
 }
@@ -0,0 +1,364 @@
+//! Derive macros for the RLP conversion traits of `aeser`.
+//!
+//! Structs are encoded positionally as an RLP list, one element per field, in declaration order.
+//! The following attributes are supported:
+//!
+//! - `#[rlp(tag = EXPR, vsn = EXPR)]` on the struct prepends an object tag and a version to the
+//!   list. Decoding fails if either of them does not match. With `any_vsn` added, decoding accepts
+//!   any version, and encoding still uses `vsn`.
+//! - `#[rlp(bytes)]` on a field encodes its serialized RLP as a byte array instead of nesting it.
+//! - `#[rlp(skip)]` on a field leaves it out of the encoding. It is set to [Default::default] when
+//!   decoding.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    parse_macro_input, parse_quote, Data, DeriveInput, Expr, Fields, GenericParam, Lifetime,
+    LifetimeParam, Member,
+};
+
+/// Derives `aeser::rlp::ToRlpItem` for a struct.
+#[proc_macro_derive(ToRlpItem, attributes(rlp))]
+pub fn derive_to_rlp_item(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    Object::parse(&input)
+        .map(|obj| obj.expand_to_rlp_item(&input))
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derives `aeser::rlp::FromRlpItem` for a struct.
+#[proc_macro_derive(FromRlpItem, attributes(rlp))]
+pub fn derive_from_rlp_item(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    Object::parse(&input)
+        .map(|obj| obj.expand_from_rlp_item(&input))
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derives `aeser::rlp::FromRlpRef` for a struct. If the struct has lifetime parameters, the
+/// first one is used as the lifetime of the decoded input.
+#[proc_macro_derive(FromRlpRef, attributes(rlp))]
+pub fn derive_from_rlp_ref(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    Object::parse(&input)
+        .map(|obj| obj.expand_from_rlp_ref(&input))
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// How a field is represented in the RLP list.
+enum FieldKind {
+    /// Nested RLP item.
+    Item,
+    /// Byte array holding the serialized RLP of the field.
+    Bytes,
+    /// Not encoded at all.
+    Skip,
+}
+
+struct Field {
+    member: Member,
+    name: String,
+    kind: FieldKind,
+}
+
+struct Object {
+    name: String,
+    header: Option<(Expr, Expr)>,
+    check_vsn: bool,
+    named: bool,
+    fields: Vec<Field>,
+}
+
+impl Object {
+    fn parse(input: &DeriveInput) -> syn::Result<Object> {
+        let data = match &input.data {
+            Data::Struct(data) => data,
+            _ => Err(syn::Error::new_spanned(
+                &input.ident,
+                "RLP conversions can only be derived for structs",
+            ))?,
+        };
+
+        if matches!(data.fields, Fields::Unit) {
+            Err(syn::Error::new_spanned(
+                &input.ident,
+                "RLP conversions cannot be derived for unit structs",
+            ))?;
+        }
+
+        let mut tag = None;
+        let mut vsn = None;
+        let mut check_vsn = true;
+        for attr in input.attrs.iter().filter(|a| a.path().is_ident("rlp")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("tag") {
+                    tag = Some(meta.value()?.parse::<Expr>()?);
+                    Ok(())
+                } else if meta.path.is_ident("vsn") {
+                    vsn = Some(meta.value()?.parse::<Expr>()?);
+                    Ok(())
+                } else if meta.path.is_ident("any_vsn") {
+                    check_vsn = false;
+                    Ok(())
+                } else {
+                    Err(meta.error("expected `tag`, `vsn` or `any_vsn`"))
+                }
+            })?;
+        }
+
+        let header = match (tag, vsn) {
+            (Some(tag), Some(vsn)) => Some((tag, vsn)),
+            (None, None) if check_vsn => None,
+            _ => Err(syn::Error::new_spanned(
+                &input.ident,
+                "`tag` and `vsn` have to be specified together, and `any_vsn` requires both",
+            ))?,
+        };
+
+        let mut fields = Vec::new();
+        for (i, field) in data.fields.iter().enumerate() {
+            let mut kind = FieldKind::Item;
+            for attr in field.attrs.iter().filter(|a| a.path().is_ident("rlp")) {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("bytes") {
+                        kind = FieldKind::Bytes;
+                        Ok(())
+                    } else if meta.path.is_ident("skip") {
+                        kind = FieldKind::Skip;
+                        Ok(())
+                    } else {
+                        Err(meta.error("expected `bytes` or `skip`"))
+                    }
+                })?;
+            }
+
+            let (member, name) = match &field.ident {
+                Some(ident) => (Member::Named(ident.clone()), ident.to_string()),
+                None => (Member::Unnamed(i.into()), i.to_string()),
+            };
+            fields.push(Field { member, name, kind });
+        }
+
+        Ok(Object {
+            name: input.ident.to_string(),
+            header,
+            check_vsn,
+            named: matches!(data.fields, Fields::Named(_)),
+            fields,
+        })
+    }
+
+    /// Number of elements in the encoded list.
+    fn encoded_len(&self) -> usize {
+        let header_len = if self.header.is_some() { 2 } else { 0 };
+        let field_len = self
+            .fields
+            .iter()
+            .filter(|f| !matches!(f.kind, FieldKind::Skip))
+            .count();
+        header_len + field_len
+    }
+
+    fn expand_to_rlp_item(&self, input: &DeriveInput) -> TokenStream2 {
+        let ident = &input.ident;
+        let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+        let header = self.header.iter().flat_map(|(tag, vsn)| {
+            [
+                quote!(::aeser::rlp::ToRlpItem::to_rlp_item(&#tag)),
+                quote!(::aeser::rlp::ToRlpItem::to_rlp_item(&#vsn)),
+            ]
+        });
+
+        let fields = self.fields.iter().filter_map(|f| {
+            let member = &f.member;
+            match f.kind {
+                FieldKind::Item => {
+                    Some(quote!(::aeser::rlp::ToRlpItem::to_rlp_item(&self.#member)))
+                }
+                FieldKind::Bytes => Some(quote!(::aeser::rlp::RlpItem::ByteArray(
+                    ::aeser::rlp::ToRlpItem::serialize_rlp(&self.#member)
+                ))),
+                FieldKind::Skip => None,
+            }
+        });
+
+        quote! {
+            impl #impl_generics ::aeser::rlp::ToRlpItem for #ident #ty_generics #where_clause {
+                fn to_rlp_item(&self) -> ::aeser::rlp::RlpItem {
+                    ::aeser::rlp::RlpItem::List(::std::vec![#(#header,)* #(#fields,)*])
+                }
+            }
+        }
+    }
+
+    fn expand_from_rlp_item(&self, input: &DeriveInput) -> TokenStream2 {
+        let ident = &input.ident;
+        let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+        let items = quote!(items);
+        let check_header =
+            self.expand_check_header(&items, quote!(::aeser::rlp::FromRlpItem::from_rlp_item));
+        let construct = self.expand_construct(&items, |item| {
+            (
+                quote!(::aeser::rlp::FromRlpItem::from_rlp_item(#item)),
+                quote! {
+                    match #item {
+                        ::aeser::rlp::RlpItem::ByteArray(bytes) =>
+                            ::aeser::rlp::FromRlpItem::deserialize_rlp(bytes),
+                        ::aeser::rlp::RlpItem::List(_) =>
+                            ::std::result::Result::Err(::aeser::error::DecodingErr::InvalidBinary),
+                    }
+                },
+            )
+        });
+        let object = &self.name;
+        let len = self.encoded_len();
+
+        quote! {
+            impl #impl_generics ::aeser::rlp::FromRlpItem for #ident #ty_generics #where_clause {
+                fn from_rlp_item(
+                    item: &::aeser::rlp::RlpItem,
+                ) -> ::std::result::Result<Self, ::aeser::error::DecodingErr> {
+                    let #items = match item {
+                        ::aeser::rlp::RlpItem::List(items) if items.len() == #len => items,
+                        _ => return ::std::result::Result::Err(
+                            ::aeser::error::DecodingErr::InvalidObject { object: #object, fields: #len }
+                        ),
+                    };
+                    #check_header
+                    ::std::result::Result::Ok(#construct)
+                }
+            }
+        }
+    }
+
+    fn expand_from_rlp_ref(&self, input: &DeriveInput) -> TokenStream2 {
+        let ident = &input.ident;
+        let (_, ty_generics, where_clause) = input.generics.split_for_impl();
+
+        // Reuse the lifetime of the struct, if there is one. Otherwise introduce a fresh one.
+        let mut generics = input.generics.clone();
+        let lifetime = match generics.lifetimes().next() {
+            Some(param) => param.lifetime.clone(),
+            None => {
+                let lifetime: Lifetime = parse_quote!('__rlp);
+                generics.params.insert(
+                    0,
+                    GenericParam::Lifetime(LifetimeParam::new(lifetime.clone())),
+                );
+                lifetime
+            }
+        };
+        let (impl_generics, _, _) = generics.split_for_impl();
+
+        let items = quote!(items);
+        let check_header =
+            self.expand_check_header(&items, quote!(::aeser::rlp::FromRlpRef::from_rlp_ref));
+        let construct = self.expand_construct(&items, |item| {
+            (
+                quote!(::aeser::rlp::FromRlpRef::from_rlp_ref(#item)),
+                quote! {
+                    #item.byte_array().and_then(::aeser::rlp::FromRlpRef::deserialize_rlp_ref)
+                },
+            )
+        });
+        let object = &self.name;
+        let len = self.encoded_len();
+
+        quote! {
+            impl #impl_generics ::aeser::rlp::FromRlpRef<#lifetime> for #ident #ty_generics #where_clause {
+                fn from_rlp_ref(
+                    item: &::aeser::rlp::RlpRef<#lifetime>,
+                ) -> ::std::result::Result<Self, ::aeser::error::DecodingErr> {
+                    let invalid = || ::aeser::error::DecodingErr::InvalidObject { object: #object, fields: #len };
+                    let #items = match item {
+                        ::aeser::rlp::RlpRef::List(list) => list.to_vec()?,
+                        ::aeser::rlp::RlpRef::ByteArray(_) => return ::std::result::Result::Err(invalid()),
+                    };
+                    if #items.len() != #len {
+                        return ::std::result::Result::Err(invalid());
+                    }
+                    #check_header
+                    ::std::result::Result::Ok(#construct)
+                }
+            }
+        }
+    }
+
+    /// Verifies the leading tag and version, if the object has them. `decode` is the path of the
+    /// function decoding a single list element.
+    fn expand_check_header(&self, items: &TokenStream2, decode: TokenStream2) -> TokenStream2 {
+        let object = &self.name;
+        let check_vsn = self.header.as_ref().filter(|_| self.check_vsn).map(|(_, vsn)| {
+            quote! {
+                if !is_expected(#decode(&#items[1]), #vsn) {
+                    return ::std::result::Result::Err(
+                        ::aeser::error::DecodingErr::InvalidVersion { object: #object }
+                    );
+                }
+            }
+        });
+        match &self.header {
+            None => quote!(),
+            Some((tag, _)) => quote! {
+                fn is_expected<T: ::std::cmp::PartialEq>(
+                    decoded: ::std::result::Result<T, ::aeser::error::DecodingErr>,
+                    expected: T,
+                ) -> bool {
+                    decoded.map_or(false, |x| x == expected)
+                }
+                if !is_expected(#decode(&#items[0]), #tag) {
+                    return ::std::result::Result::Err(
+                        ::aeser::error::DecodingErr::InvalidTag { object: #object }
+                    );
+                }
+                #check_vsn
+            },
+        }
+    }
+
+    /// Builds `Self` out of decoded list elements. `decoders` returns expressions decoding an
+    /// element as a nested item and as a serialized byte array respectively.
+    fn expand_construct<F>(&self, items: &TokenStream2, decoders: F) -> TokenStream2
+    where
+        F: Fn(&TokenStream2) -> (TokenStream2, TokenStream2),
+    {
+        let object = &self.name;
+        let mut idx: usize = if self.header.is_some() { 2 } else { 0 };
+
+        let values: Vec<TokenStream2> = self
+            .fields
+            .iter()
+            .map(|f| {
+                let field = &f.name;
+                let item = quote!((&#items[#idx]));
+                let (decode_item, decode_bytes) = decoders(&item);
+                let decode = match f.kind {
+                    FieldKind::Skip => return quote!(::std::default::Default::default()),
+                    FieldKind::Item => decode_item,
+                    FieldKind::Bytes => decode_bytes,
+                };
+                idx += 1;
+                quote! {
+                    (#decode).map_err(|err| ::aeser::error::DecodingErr::InvalidField {
+                        object: #object,
+                        field: #field,
+                        err: ::std::boxed::Box::new(err),
+                    })?
+                }
+            })
+            .collect();
+
+        if self.named {
+            let members = self.fields.iter().map(|f| &f.member);
+            quote!(Self { #(#members: #values,)* })
+        } else {
+            quote!(Self(#(#values,)*))
+        }
+    }
+}
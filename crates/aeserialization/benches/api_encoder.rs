@@ -0,0 +1,104 @@
+use aeser::api_encoder::{self, Encoder, KnownType};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+fn make_check(data: &[u8]) -> Vec<u8> {
+    use sha2::{Digest, Sha256};
+    Sha256::digest(Sha256::digest(data))[..4].to_vec()
+}
+
+/// Encoding allocating the prefix, the checksummed payload and the encoded payload separately, as
+/// done before encoding into buffers. Kept as a baseline.
+fn allocating_encode(t: KnownType, payload: &[u8]) -> String {
+    let pfx = t.prefix().to_string();
+    let data_c = [payload, &make_check(payload)].concat();
+    let enc = bs58::encode(&data_c).into_string();
+    [&pfx, "_", &enc].concat()
+}
+
+/// Decoding copying the payload out of the input before decoding it. Kept as a baseline.
+fn allocating_decode(data: &str) -> (KnownType, Vec<u8>) {
+    let (pfx, payload) = data.split_once('_').unwrap();
+    let tp = KnownType::from_prefix(pfx).unwrap();
+    let payload = payload.to_string();
+    let dec = bs58::decode(&payload).into_vec().unwrap();
+    let (body, check) = dec.split_at(dec.len() - 4);
+    assert_eq!(check, make_check(body));
+    (tp, body.to_vec())
+}
+
+fn accounts(n: usize) -> Vec<[u8; 32]> {
+    (0..n)
+        .map(|i| {
+            let mut key = [0; 32];
+            key[..8].copy_from_slice(&(i as u64).to_be_bytes());
+            key
+        })
+        .collect()
+}
+
+fn encoding(c: &mut Criterion) {
+    let keys = accounts(1000);
+    let tp = KnownType::AccountPubkey;
+    assert_eq!(allocating_encode(tp, &keys[0]), api_encoder::encode_data(tp, &keys[0]));
+
+    let mut group = c.benchmark_group("encode 1000 accounts");
+    group.bench_function("allocating", |b| {
+        b.iter(|| {
+            for key in &keys {
+                black_box(allocating_encode(tp, black_box(key)));
+            }
+        })
+    });
+    group.bench_function("encode_data", |b| {
+        b.iter(|| {
+            for key in &keys {
+                black_box(api_encoder::encode_data(tp, black_box(key)));
+            }
+        })
+    });
+    group.bench_function("reused buffers", |b| {
+        let mut encoder = Encoder::new();
+        let mut out = String::new();
+        b.iter(|| {
+            for key in &keys {
+                out.clear();
+                encoder.encode_into(tp, black_box(key), &mut out);
+            }
+        })
+    });
+    group.bench_function("batch", |b| {
+        b.iter(|| api_encoder::encode_batch(tp, black_box(&keys)))
+    });
+    group.finish();
+
+    let encoded = api_encoder::encode_batch(tp, &keys);
+
+    let mut group = c.benchmark_group("decode 1000 accounts");
+    group.bench_function("allocating", |b| {
+        b.iter(|| {
+            for enc in &encoded {
+                black_box(allocating_decode(black_box(enc)));
+            }
+        })
+    });
+    group.bench_function("decode", |b| {
+        b.iter(|| {
+            for enc in &encoded {
+                black_box(api_encoder::decode(black_box(enc)).unwrap());
+            }
+        })
+    });
+    group.bench_function("reused buffer", |b| {
+        let mut out = Vec::new();
+        b.iter(|| {
+            for enc in &encoded {
+                out.clear();
+                api_encoder::decode_into(black_box(enc), &mut out).unwrap();
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, encoding);
+criterion_main!(benches);
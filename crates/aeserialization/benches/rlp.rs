@@ -0,0 +1,92 @@
+use aeser::contract_code::{Code, TypeInfo};
+use aeser::rlp::{RlpItem, ToRlpItem};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+/// Serialization building every list payload separately and copying it behind its size
+/// description, as done before list sizes were precomputed. Kept as a baseline.
+fn nested_serialize(item: &RlpItem) -> Vec<u8> {
+    let with_header = |offset: u8, payload: Vec<u8>| {
+        let header = if payload.len() <= 55 {
+            vec![offset + payload.len() as u8]
+        } else {
+            let size = payload.len().to_be_bytes();
+            let zeros = payload.len().leading_zeros() as usize / 8;
+            [
+                &[offset + 55 + (size.len() - zeros) as u8][..],
+                &size[zeros..],
+            ]
+            .concat()
+        };
+        [header, payload].concat()
+    };
+
+    match item {
+        RlpItem::ByteArray(bytes) if bytes.len() == 1 && bytes[0] < 128 => bytes.clone(),
+        RlpItem::ByteArray(bytes) => with_header(128, bytes.clone()),
+        RlpItem::List(items) => with_header(192, items.iter().flat_map(nested_serialize).collect()),
+    }
+}
+
+fn large_code() -> Code {
+    let type_info = (0..2000u32)
+        .map(|i| TypeInfo {
+            type_hash: i.to_be_bytes().repeat(8),
+            name: format!("entrypoint_{i}").into_bytes(),
+            payable: i % 2 == 0,
+            arg_type: vec![i as u8; 64],
+            out_type: vec![i as u8; 16],
+        })
+        .collect();
+
+    Code {
+        source_hash: vec![7; 32],
+        type_info,
+        byte_code: vec![42; 256 * 1024],
+        compiler_version: b"8.0.0".to_vec(),
+        payable: true,
+    }
+}
+
+/// Nested lists resembling a Merkle proof, each level holding a few hashes.
+fn deep_list(depth: usize) -> RlpItem {
+    (0..depth).fold(RlpItem::ByteArray(vec![1; 32]), |acc, i| {
+        RlpItem::List(vec![
+            RlpItem::ByteArray(vec![i as u8; 32]),
+            acc,
+            RlpItem::ByteArray(vec![!(i as u8); 32]),
+        ])
+    })
+}
+
+fn serialization(c: &mut Criterion) {
+    let code = large_code();
+    let code_item = code.to_rlp_item();
+    assert_eq!(nested_serialize(&code_item), code_item.serialize());
+
+    let mut group = c.benchmark_group("large code");
+    group.bench_function("nested", |b| {
+        b.iter(|| nested_serialize(black_box(&code_item)))
+    });
+    group.bench_function("single pass", |b| {
+        b.iter(|| black_box(&code_item).serialize())
+    });
+    group.bench_function("into buffer", |b| {
+        let mut out = Vec::with_capacity(code_item.encoded_len());
+        b.iter(|| {
+            out.clear();
+            black_box(&code_item).serialize_into(&mut out);
+        })
+    });
+    group.finish();
+
+    let deep = deep_list(200);
+    assert_eq!(nested_serialize(&deep), deep.serialize());
+
+    let mut group = c.benchmark_group("deep list");
+    group.bench_function("nested", |b| b.iter(|| nested_serialize(black_box(&deep))));
+    group.bench_function("single pass", |b| b.iter(|| black_box(&deep).serialize()));
+    group.finish();
+}
+
+criterion_group!(benches, serialization);
+criterion_main!(benches);
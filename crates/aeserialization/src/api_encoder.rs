@@ -2,70 +2,103 @@ use crate::error::DecodingErr;
 use crate::id;
 use crate::Bytes;
 
-/// Possible chain-object types.
-#[derive(Debug, Copy, Clone, PartialEq)]
-pub enum KnownType {
-    KeyBlockHash,
-    MicroBlockHash,
-    BlockPofHash,
-    BlockTxHash,
-    BlockStateHash,
-    Channel,
-    ContractBytearray,
-    ContractPubkey,
-    ContractStoreKey,
-    ContractStoreValue,
-    Transaction,
-    TxHash,
-    OraclePubkey,
-    OracleQuery,
-    OracleQueryId,
-    OracleResponse,
-    AccountPubkey,
-    AccountSeckey,
-    Signature,
-    Name,
-    Commitment,
-    PeerPubkey,
-    State,
-    Poi,
-    StateTrees,
-    CallStateTree,
-    Bytearray,
+pub mod typed;
+
+pub use typed::*;
+
+/// Properties of a [KnownType].
+struct TypeSpec {
+    prefix: &'static str,
+    byte_size: Option<usize>,
+    encoding: Encoding,
+    id_tag: Option<id::Tag>,
+}
+
+/// Defines [KnownType] along with its properties, so that all conversions are derived from a
+/// single table.
+macro_rules! known_types {
+    ($($tp:ident => $prefix:literal, $size:expr, $encoding:ident, $id_tag:expr;)*) => {
+        /// Possible chain-object types.
+        #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+        pub enum KnownType {
+            $($tp,)*
+        }
+
+        impl KnownType {
+            /// All known types.
+            pub const ALL: &'static [KnownType] = &[$(KnownType::$tp,)*];
+
+            /// Parses the type from a prefix. See [KnownType::prefix] for more details.
+            pub fn from_prefix(prefix: &str) -> Option<KnownType> {
+                match prefix {
+                    $($prefix => Some(KnownType::$tp),)*
+                    _ => None,
+                }
+            }
+
+            fn spec(self) -> TypeSpec {
+                match self {
+                    $(KnownType::$tp => TypeSpec {
+                        prefix: $prefix,
+                        byte_size: $size,
+                        encoding: Encoding::$encoding,
+                        id_tag: $id_tag,
+                    },)*
+                }
+            }
+        }
+    };
 }
 
+known_types! {
+    KeyBlockHash       => "kh", Some(32), Base58, None;
+    MicroBlockHash     => "mh", Some(32), Base58, None;
+    BlockPofHash       => "bf", Some(32), Base58, None;
+    BlockTxHash        => "bx", Some(32), Base58, None;
+    BlockStateHash     => "bs", Some(32), Base58, None;
+    Channel            => "ch", Some(32), Base58, Some(id::Tag::Channel);
+    ContractBytearray  => "cb", None,     Base64, None;
+    ContractPubkey     => "ct", Some(32), Base58, Some(id::Tag::Contract);
+    ContractStoreKey   => "ck", None,     Base64, None;
+    ContractStoreValue => "cv", None,     Base64, None;
+    ContractSource     => "cx", None,     Base64, None;
+    Transaction        => "tx", None,     Base64, None;
+    TxHash             => "th", Some(32), Base58, None;
+    OraclePubkey       => "ok", Some(32), Base58, Some(id::Tag::Oracle);
+    OracleQuery        => "ov", None,     Base64, None;
+    OracleQueryId      => "oq", Some(32), Base58, None;
+    OracleResponse     => "or", None,     Base64, None;
+    AccountPubkey      => "ak", Some(32), Base58, Some(id::Tag::Account);
+    AccountSeckey      => "sk", Some(32), Base58, None;
+    AssociateChain     => "ac", Some(32), Base58, Some(id::Tag::AssociateChain);
+    Signature          => "sg", Some(64), Base58, None;
+    Name               => "nm", None,     Base58, Some(id::Tag::Name);
+    NativeToken        => "an", Some(32), Base58, Some(id::Tag::NativeToken);
+    Commitment         => "cm", Some(32), Base58, Some(id::Tag::Commitment);
+    PeerPubkey         => "pp", Some(32), Base58, None;
+    State              => "st", Some(32), Base64, None;
+    Poi                => "pi", None,     Base64, None;
+    StateTrees         => "ss", None,     Base64, None;
+    CallStateTree      => "cs", None,     Base64, None;
+    Bytearray          => "ba", None,     Base64, None;
+}
+
+/// Types which can be encoded as ids. See [id::Tag].
+pub const ID_TYPES: &[KnownType] = &[
+    KnownType::AccountPubkey,
+    KnownType::AssociateChain,
+    KnownType::Channel,
+    KnownType::Commitment,
+    KnownType::ContractPubkey,
+    KnownType::Name,
+    KnownType::NativeToken,
+    KnownType::OraclePubkey,
+];
+
 impl KnownType {
     /// Payload size for a given type. Returns [None] is the size is not fixed.
     pub fn byte_size(self) -> Option<usize> {
-        match self {
-            KnownType::KeyBlockHash => Some(32),
-            KnownType::MicroBlockHash => Some(32),
-            KnownType::BlockPofHash => Some(32),
-            KnownType::BlockTxHash => Some(32),
-            KnownType::BlockStateHash => Some(32),
-            KnownType::Channel => Some(32),
-            KnownType::ContractPubkey => Some(32),
-            KnownType::ContractBytearray => None,
-            KnownType::ContractStoreKey => None,
-            KnownType::ContractStoreValue => None,
-            KnownType::Transaction => None,
-            KnownType::TxHash => Some(32),
-            KnownType::OraclePubkey => Some(32),
-            KnownType::OracleQuery => None,
-            KnownType::OracleQueryId => Some(32),
-            KnownType::OracleResponse => None,
-            KnownType::AccountPubkey => Some(32),
-            KnownType::AccountSeckey => Some(32),
-            KnownType::Signature => Some(64),
-            KnownType::Name => None,
-            KnownType::Commitment => Some(32),
-            KnownType::PeerPubkey => Some(32),
-            KnownType::State => Some(32),
-            KnownType::Poi => None,
-            KnownType::StateTrees => None,
-            KnownType::CallStateTree => None,
-            KnownType::Bytearray => None,
-        }
+        self.spec().byte_size
     }
 
     /// Validates payload size. Returns [true] when the size for the type matches or the type does
@@ -79,135 +112,31 @@ impl KnownType {
 
     /// Returns a prefix describing the type. This prefix is prepended to the encoded payload and
     /// separated with a single '_' character.
-    pub fn prefix(self) -> String {
-        let s = match self {
-            KnownType::KeyBlockHash => "kh",
-            KnownType::MicroBlockHash => "mh",
-            KnownType::BlockPofHash => "bf",
-            KnownType::BlockTxHash => "bx",
-            KnownType::BlockStateHash => "bs",
-            KnownType::Channel => "ch",
-            KnownType::ContractPubkey => "ct",
-            KnownType::ContractBytearray => "cb",
-            KnownType::ContractStoreKey => "ck",
-            KnownType::ContractStoreValue => "cv",
-            KnownType::Transaction => "tx",
-            KnownType::TxHash => "th",
-            KnownType::OraclePubkey => "ok",
-            KnownType::OracleQuery => "ov",
-            KnownType::OracleQueryId => "oq",
-            KnownType::OracleResponse => "or",
-            KnownType::AccountPubkey => "ak",
-            KnownType::AccountSeckey => "sk",
-            KnownType::Signature => "sg",
-            KnownType::Commitment => "cm",
-            KnownType::PeerPubkey => "pp",
-            KnownType::Name => "nm",
-            KnownType::State => "st",
-            KnownType::Poi => "pi",
-            KnownType::StateTrees => "ss",
-            KnownType::CallStateTree => "cs",
-            KnownType::Bytearray => "ba",
-        };
-        String::from(s)
-    }
-
-    /// Parses the type from a prefix. See [to_prefix] for more details.
-    pub fn from_prefix(prefix: &str) -> Option<KnownType> {
-        use KnownType::*;
-        match prefix {
-            "kh" => Some(KeyBlockHash),
-            "mh" => Some(MicroBlockHash),
-            "bf" => Some(BlockPofHash),
-            "bx" => Some(BlockTxHash),
-            "bs" => Some(BlockStateHash),
-            "ch" => Some(Channel),
-            "cb" => Some(ContractBytearray),
-            "ck" => Some(ContractStoreKey),
-            "cv" => Some(ContractStoreValue),
-            "ct" => Some(ContractPubkey),
-            "tx" => Some(Transaction),
-            "th" => Some(TxHash),
-            "ok" => Some(OraclePubkey),
-            "ov" => Some(OracleQuery),
-            "oq" => Some(OracleQueryId),
-            "or" => Some(OracleResponse),
-            "ak" => Some(AccountPubkey),
-            "sk" => Some(AccountSeckey),
-            "sg" => Some(Signature),
-            "cm" => Some(Commitment),
-            "pp" => Some(PeerPubkey),
-            "nm" => Some(Name),
-            "st" => Some(State),
-            "pi" => Some(Poi),
-            "ss" => Some(StateTrees),
-            "cs" => Some(CallStateTree),
-            "ba" => Some(Bytearray),
-            _ => None,
-        }
+    pub fn prefix(self) -> &'static str {
+        self.spec().prefix
     }
 
     fn to_id_tag(self) -> Option<id::Tag> {
-        use id::Tag;
-        match self {
-            KnownType::AccountPubkey => Some(Tag::Account),
-            KnownType::Channel => Some(Tag::Channel),
-            KnownType::Commitment => Some(Tag::Commitment),
-            KnownType::ContractPubkey => Some(Tag::Contract),
-            KnownType::Name => Some(Tag::Name),
-            KnownType::OraclePubkey => Some(Tag::Oracle),
-            _ => None,
-        }
+        self.spec().id_tag
     }
 
     fn from_id_tag(tag: id::Tag) -> KnownType {
-        use id::Tag;
-        use KnownType::*;
-        match tag {
-            Tag::Account => AccountPubkey,
-            Tag::Channel => Channel,
-            Tag::Commitment => Commitment,
-            Tag::Contract => ContractPubkey,
-            Tag::Name => Name,
-            Tag::Oracle => OraclePubkey,
-        }
+        KnownType::ALL
+            .iter()
+            .copied()
+            .find(|tp| tp.to_id_tag() == Some(tag))
+            .expect("id tag without a type")
     }
 
     /// Describes how payload is encoded.
     pub fn encoding(self) -> Encoding {
-        use Encoding::*;
-        match self {
-            KnownType::KeyBlockHash => Base58,
-            KnownType::MicroBlockHash => Base58,
-            KnownType::BlockPofHash => Base58,
-            KnownType::BlockTxHash => Base58,
-            KnownType::BlockStateHash => Base58,
-            KnownType::Channel => Base58,
-            KnownType::ContractPubkey => Base58,
-            KnownType::ContractBytearray => Base64,
-            KnownType::ContractStoreKey => Base64,
-            KnownType::ContractStoreValue => Base64,
-            KnownType::Transaction => Base64,
-            KnownType::TxHash => Base58,
-            KnownType::OraclePubkey => Base58,
-            KnownType::OracleQuery => Base64,
-            KnownType::OracleQueryId => Base58,
-            KnownType::OracleResponse => Base64,
-            KnownType::AccountSeckey => Base58,
-            KnownType::AccountPubkey => Base58,
-            KnownType::Signature => Base58,
-            KnownType::Commitment => Base58,
-            KnownType::PeerPubkey => Base58,
-            KnownType::Name => Base58,
-            KnownType::State => Base64,
-            KnownType::Poi => Base64,
-            KnownType::StateTrees => Base64,
-            KnownType::CallStateTree => Base64,
-            KnownType::Bytearray => Base64,
-        }
+        self.spec().encoding
     }
 }
 
+/// Size of the checksum appended to encoded payloads.
+const CHECK_SIZE: usize = 4;
+
 /// Supported types of encoding.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum Encoding {
@@ -216,7 +145,7 @@ pub enum Encoding {
 }
 
 impl Encoding {
-    fn make_check(data: &[u8]) -> Bytes {
+    fn make_check(data: &[u8]) -> [u8; CHECK_SIZE] {
         use sha2::{Digest, Sha256};
         let mut hasher = <Sha256 as Digest>::new();
 
@@ -224,69 +153,180 @@ impl Encoding {
         let check1 = hasher.finalize_reset();
         hasher.update(check1);
         let check2 = hasher.finalize_reset();
-        check2[..4].to_vec()
+        check2[..CHECK_SIZE].try_into().expect("digest shorter than a checksum")
     }
 
-    fn add_check(self, data: &[u8]) -> Bytes {
-        let c = Self::make_check(data);
-        vec![data, &c].concat()
-    }
-
-    fn encode(self, data: &[u8]) -> String {
+    /// Appends the encoding of the data to the output.
+    fn encode_into(self, data: &[u8], out: &mut String) {
         match self {
-            Encoding::Base58 =>
-                bs58::encode(data).into_string(),
+            Encoding::Base58 => {
+                bs58::encode(data)
+                    .onto(&mut *out)
+                    .expect("appending to a string cannot fail");
+            }
             Encoding::Base64 => {
                 use base64::Engine;
                 let engine = base64::engine::general_purpose::STANDARD;
-                engine.encode(data)
+                engine.encode_string(data, out)
             }
         }
     }
 
-    fn encode_with_check(self, data: &[u8]) -> String {
-        let data_c = self.add_check(data);
-        self.encode(&data_c)
-    }
-
-    fn decode(self, data: &str) -> Option<Bytes> {
-        match self {
-            Encoding::Base58 =>
-                bs58::decode(data).into_vec().ok(),
+    /// Appends the decoded payload to the output. Positions of invalid characters are reported
+    /// relative to `offset`. The output is left intact on failure.
+    fn decode_into(self, data: &str, offset: usize, out: &mut Bytes) -> Result<(), DecodingErr> {
+        let start = out.len();
+        let res = match self {
+            Encoding::Base58 => bs58::decode(data).onto(&mut *out).map(|_| ()).map_err(|e| match e {
+                bs58::decode::Error::InvalidCharacter { index, .. } => {
+                    DecodingErr::InvalidCharacter { position: offset + index }
+                }
+                _ => DecodingErr::InvalidEncoding,
+            }),
             Encoding::Base64 => {
-                use base64::Engine;
+                use base64::{DecodeError, Engine};
                 let engine = base64::engine::general_purpose::STANDARD;
-                engine.decode(data).ok()
+                engine.decode_vec(data, out).map_err(|e| match e {
+                    DecodeError::InvalidByte(index, _) | DecodeError::InvalidLastSymbol(index, _) => {
+                        DecodingErr::InvalidCharacter { position: offset + index }
+                    }
+                    _ => DecodingErr::InvalidEncoding,
+                })
             }
+        };
+        if res.is_err() {
+            out.truncate(start);
         }
+        res
+    }
+}
+
+/// Encodes objects reusing an internal buffer, which avoids allocations when encoding many objects
+/// into a reused output.
+#[derive(Debug, Default)]
+pub struct Encoder {
+    buf: Bytes,
+}
+
+impl Encoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends the encoding of raw data to the output. Includes a checksum.
+    pub fn encode_into(&mut self, t: KnownType, payload: &[u8], out: &mut String) {
+        self.buf.clear();
+        self.buf.extend_from_slice(payload);
+        self.buf.extend_from_slice(&Encoding::make_check(payload));
+
+        out.push_str(t.prefix());
+        out.push('_');
+        t.encoding().encode_into(&self.buf, out);
+    }
+
+    /// Encodes raw data accordingly to the type. Includes a checksum.
+    pub fn encode(&mut self, t: KnownType, payload: &[u8]) -> String {
+        let mut out = String::new();
+        self.encode_into(t, payload, &mut out);
+        out
     }
 }
 
 /// Encodes raw data accordingly to the type. Includes a checksum.
 pub fn encode_data(t: KnownType, payload: &[u8]) -> String {
-    let pfx = t.prefix();
-    let enc = t.encoding().encode_with_check(payload);
-    [&pfx, "_", &enc].concat()
+    Encoder::new().encode(t, payload)
+}
+
+/// Appends the encoding of raw data to the output. Includes a checksum. See [Encoder] for encoding
+/// many objects.
+pub fn encode_into(t: KnownType, payload: &[u8], out: &mut String) {
+    Encoder::new().encode_into(t, payload, out)
+}
+
+/// Encodes many objects of the same type, reusing a single buffer.
+pub fn encode_batch<I>(t: KnownType, payloads: I) -> Vec<String>
+where
+    I: IntoIterator,
+    I::Item: AsRef<[u8]>,
+{
+    let mut encoder = Encoder::new();
+    payloads
+        .into_iter()
+        .map(|x| encoder.encode(t, x.as_ref()))
+        .collect()
 }
 
 /// Encodes an id. Includes a checksum.
 pub fn encode_id(id: &id::Id) -> String {
-    encode_data(KnownType::from_id_tag(id.tag), &id.val.bytes)
+    encode_data(KnownType::from_id_tag(id.tag), id.val.as_bytes())
 }
 
 /// Decodes raw data according to the prefixed type.
 pub fn decode(data: &str) -> Result<(KnownType, Bytes), DecodingErr> {
+    let mut decoded = Vec::new();
+    let tp = decode_into(data, &mut decoded)?;
+    Ok((tp, decoded))
+}
+
+/// Appends raw data decoded according to the prefixed type to the output. Returns the type. The
+/// output is left intact on failure.
+pub fn decode_into(data: &str, out: &mut Bytes) -> Result<KnownType, DecodingErr> {
     let (tp, payload) = split_prefix(data)?;
-    let decoded = decode_check(tp, &payload)?;
+    let start = out.len();
+    decode_check_into(tp, payload, PREFIX_LEN, out)?;
 
-    if !tp.check_size(decoded.len()) {
-        Err(DecodingErr::IncorrectSize)?;
+    if let Err(e) = check_type_size(tp, out.len() - start) {
+        out.truncate(start);
+        Err(e)?;
     }
 
-    Ok((tp, decoded))
+    Ok(tp)
+}
+
+/// Decodes many objects, stopping at the first failure. Payloads are decoded into a single reused
+/// buffer and copied out of it.
+pub fn decode_batch<'a, I>(data: I) -> Result<Vec<(KnownType, Bytes)>, DecodingErr>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let mut buf = Vec::new();
+    data.into_iter()
+        .map(|x| {
+            buf.clear();
+            let tp = decode_into(x, &mut buf)?;
+            Ok((tp, buf.clone()))
+        })
+        .collect()
+}
+
+/// Checks whether the data is a well-formed encoding of the given type. Fails early on a prefix
+/// mismatch, before decoding the payload.
+pub fn is_valid(data: &str, tp: KnownType) -> bool {
+    match data.split_once('_') {
+        Some((pfx, payload)) if pfx == tp.prefix() => {
+            let mut decoded = Vec::new();
+            decode_check_into(tp, payload, PREFIX_LEN, &mut decoded).is_ok()
+                && tp.check_size(decoded.len())
+        }
+        _ => false,
+    }
 }
 
-fn split_prefix(data: &str) -> Result<(KnownType, String), DecodingErr> {
+/// Length of the type prefix together with the separator.
+const PREFIX_LEN: usize = 3;
+
+pub(crate) fn check_type_size(tp: KnownType, size: usize) -> Result<(), DecodingErr> {
+    match tp.byte_size() {
+        Some(expected) if expected != size => Err(DecodingErr::InvalidTypeSize {
+            tp,
+            expected,
+            actual: size,
+        }),
+        _ => Ok(()),
+    }
+}
+
+fn split_prefix(data: &str) -> Result<(KnownType, &str), DecodingErr> {
     let (pfx, payload) = data.split_once('_').ok_or(DecodingErr::MissingPrefix)?;
 
     if pfx.len() != 2 {
@@ -295,25 +335,51 @@ fn split_prefix(data: &str) -> Result<(KnownType, String), DecodingErr> {
 
     let tp = KnownType::from_prefix(pfx).ok_or(DecodingErr::InvalidPrefix)?;
 
-    Ok((tp, payload.to_string()))
+    Ok((tp, payload))
 }
 
+/// Decodes a payload without the type prefix and verifies its checksum. Does not check the size.
 pub fn decode_check(tp: KnownType, data: &str) -> Result<Bytes, DecodingErr> {
-    let dec = tp
-        .encoding()
-        .decode(data)
-        .ok_or(DecodingErr::InvalidEncoding)?;
-
-    let body_size = dec.len() - 4;
-    let body = &dec[0..body_size];
-    let check = &dec[body_size..body_size + 4];
-    let made_check = Encoding::make_check(body);
-
-    if check != made_check {
-        Err(DecodingErr::InvalidCheck)?;
+    let mut decoded = Vec::new();
+    decode_check_into(tp, data, 0, &mut decoded)?;
+    Ok(decoded)
+}
+
+/// Appends a payload decoded without the type prefix to the output, and verifies its checksum. The
+/// output is left intact on failure.
+fn decode_check_into(
+    tp: KnownType,
+    data: &str,
+    offset: usize,
+    out: &mut Bytes,
+) -> Result<(), DecodingErr> {
+    let start = out.len();
+    tp.encoding().decode_into(data, offset, out)?;
+
+    let res = check_tail(&out[start..]);
+    match res {
+        Ok(body_size) => out.truncate(start + body_size),
+        Err(_) => out.truncate(start),
+    }
+    res.map(|_| ())
+}
+
+/// Verifies the checksum at the end of decoded data. Returns the size of the data before it.
+fn check_tail(dec: &[u8]) -> Result<usize, DecodingErr> {
+    let body_size = dec
+        .len()
+        .checked_sub(CHECK_SIZE)
+        .ok_or(DecodingErr::MissingCheck)?;
+    let made_check = Encoding::make_check(&dec[..body_size]);
+
+    if dec[body_size..] != made_check[..] {
+        Err(DecodingErr::InvalidCheck {
+            expected: made_check,
+            actual: dec[body_size..].try_into().expect("checksum of an invalid size"),
+        })?;
     }
 
-    Ok(body.to_vec())
+    Ok(body_size)
 }
 
 /// Decodes data as an id.
@@ -322,17 +388,14 @@ pub fn decode_id(allowed_types: &[KnownType], data: &str) -> Result<id::Id, Deco
 
     let val: [u8; 32] = decoded
         .try_into()
-        .map_err(|_| DecodingErr::InvalidEncoding)?;
+        .map_err(|_| DecodingErr::InvalidIdSize)?;
 
     if !allowed_types.contains(&tp) {
         Err(DecodingErr::InvalidPrefix)?;
     }
 
-    let id = id::Id {
-        tag: tp.to_id_tag().ok_or(DecodingErr::InvalidPrefix)?,
-        val: id::EncodedId{bytes: val}
-    };
-    Ok(id)
+    let tag = tp.to_id_tag().ok_or(DecodingErr::InvalidPrefix)?;
+    Ok(id::Id::new(tag, val))
 }
 
 /// Decodes a block hash. Requires an adequate prefix.
@@ -354,34 +417,7 @@ mod tests {
         type Parameters = ();
         type Strategy = BoxedStrategy<Self>;
         fn arbitrary_with(_args: Self::Parameters) -> Self::Strategy {
-            prop_oneof![
-                Just(KnownType::KeyBlockHash),
-                Just(KnownType::MicroBlockHash),
-                Just(KnownType::BlockPofHash),
-                Just(KnownType::BlockTxHash),
-                Just(KnownType::BlockStateHash),
-                Just(KnownType::Channel),
-                Just(KnownType::ContractBytearray),
-                Just(KnownType::ContractPubkey),
-                Just(KnownType::ContractStoreKey),
-                Just(KnownType::ContractStoreValue),
-                Just(KnownType::Transaction),
-                Just(KnownType::TxHash),
-                Just(KnownType::OraclePubkey),
-                Just(KnownType::OracleQuery),
-                Just(KnownType::OracleQueryId),
-                Just(KnownType::OracleResponse),
-                Just(KnownType::AccountPubkey),
-                Just(KnownType::Signature),
-                Just(KnownType::Name),
-                Just(KnownType::Commitment),
-                Just(KnownType::PeerPubkey),
-                Just(KnownType::State),
-                Just(KnownType::Poi),
-                Just(KnownType::StateTrees),
-                Just(KnownType::CallStateTree),
-                Just(KnownType::Bytearray),
-            ].boxed()
+            prop::sample::select(KnownType::ALL).boxed()
         }
     }
 
@@ -429,7 +465,7 @@ mod tests {
     proptest! {
         #[test]
         fn prefix_roundtrip(tp: KnownType) {
-            let tp1 = KnownType::from_prefix(&tp.prefix());
+            let tp1 = KnownType::from_prefix(tp.prefix());
             prop_assert_eq!(Some(tp), tp1);
         }
 
@@ -457,7 +493,7 @@ mod tests {
                 any::<id::Tag>().prop_flat_map(|tag| (Just(tag), known_types_with(KnownType::from_id_tag(tag), 5))))
             {
 
-                let id = id::Id{tag: t, val: id::EncodedId{bytes: val}};
+                let id = id::Id::new(t, val);
             let enc = encode_id(&id);
             let dec = decode_id(&allowed_types, &enc).expect("Decoding id failed");
             prop_assert_eq!(id, dec);
@@ -469,11 +505,106 @@ mod tests {
             (t, allowed_types) in
                 any::<id::Tag>().prop_flat_map(|tag| (Just(tag), known_types_without(KnownType::from_id_tag(tag), 5))))
             {
-                let id = id::Id{tag: t, val: id::EncodedId{bytes: val}};
+                let id = id::Id::new(t, val);
             let enc = encode_id(&id);
             let dec = decode_id(&allowed_types, &enc);
             prop_assert_eq!(Err(DecodingErr::InvalidPrefix), dec);
         }
 
+        #[test]
+        fn decoding_arbitrary_input(data in "[a-z]{2}_[a-zA-Z0-9+/=]{0,12}") {
+            let _ = decode(&data);
+        }
+
+        #[test]
+        fn encoding_into_buffers((tp, data) in valid_data()) {
+            let mut enc = String::from("x");
+            encode_into(tp, &data, &mut enc);
+            prop_assert_eq!(&enc[1..], encode_data(tp, &data));
+
+            let mut dec = vec![7];
+            prop_assert_eq!(decode_into(&enc[1..], &mut dec), Ok(tp));
+            prop_assert_eq!(&dec[1..], &data[..]);
+        }
+
+        #[test]
+        fn batch_roundtrip(data in prop::collection::vec(any::<[u8; 32]>(), 0..8)) {
+            let enc = encode_batch(KnownType::AccountPubkey, &data);
+            let dec = decode_batch(enc.iter().map(|x| x.as_str())).expect("Batch decoding failed");
+            let expect: Vec<_> = data.iter().map(|x| (KnownType::AccountPubkey, x.to_vec())).collect();
+            prop_assert_eq!(dec, expect);
+        }
+
+        #[test]
+        fn validation((tp, data) in valid_data(), other: KnownType) {
+            let enc = encode_data(tp, &data);
+            prop_assert!(is_valid(&enc, tp));
+            prop_assert_eq!(is_valid(&enc, other), other == tp);
+        }
+    }
+
+    #[test]
+    fn failed_decoding_into_buffer() {
+        let mut out = vec![1, 2, 3];
+        let enc = encode_data(KnownType::TxHash, &[1; 31]);
+        assert!(decode_into(&enc, &mut out).is_err());
+        assert!(decode_into("ak_11l1", &mut out).is_err());
+        assert!(decode_into("tx_AAAA", &mut out).is_err());
+        assert_eq!(out, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn decoding_errors() {
+        assert_eq!(decode("ak1"), Err(DecodingErr::MissingPrefix));
+        assert_eq!(decode("xx_1"), Err(DecodingErr::InvalidPrefix));
+        assert_eq!(decode("ak_1"), Err(DecodingErr::MissingCheck));
+        assert_eq!(decode("tx_"), Err(DecodingErr::MissingCheck));
+        assert_eq!(decode("ak_11l1"), Err(DecodingErr::InvalidCharacter { position: 5 }));
+        assert_eq!(decode("tx_AA*A"), Err(DecodingErr::InvalidCharacter { position: 5 }));
+
+        let mut enc = encode_data(KnownType::Transaction, &[1, 2, 3]).into_bytes();
+        enc[4] = if enc[4] == b'A' { b'B' } else { b'A' };
+        let enc = String::from_utf8(enc).unwrap();
+        assert!(matches!(decode(&enc), Err(DecodingErr::InvalidCheck { .. })));
+        assert!(!is_valid(&enc, KnownType::Transaction));
+
+        let enc = encode_data(KnownType::TxHash, &[1; 31]);
+        assert_eq!(
+            decode(&enc),
+            Err(DecodingErr::InvalidTypeSize {
+                tp: KnownType::TxHash,
+                expected: 32,
+                actual: 31
+            })
+        );
+        assert!(!is_valid(&enc, KnownType::TxHash));
+    }
+
+    #[test]
+    fn type_table() {
+        for &tp in KnownType::ALL {
+            let pfx = tp.prefix();
+            assert_eq!(pfx.len(), 2, "{tp:?}");
+            assert_eq!(KnownType::from_prefix(pfx), Some(tp), "{tp:?}");
+
+            let size = tp.byte_size().unwrap_or(17);
+            let data: Bytes = (0..size).map(|x| x as u8).collect();
+            let enc = encode_data(tp, &data);
+            assert_eq!(decode(&enc), Ok((tp, data)), "{tp:?}");
+
+            if tp.byte_size().is_some() {
+                let enc = encode_data(tp, &[1; 65]);
+                let res = decode(&enc);
+                assert!(matches!(res, Err(DecodingErr::InvalidTypeSize { actual: 65, .. })));
+            }
+
+            match tp.to_id_tag() {
+                Some(tag) => {
+                    assert!(ID_TYPES.contains(&tp), "{tp:?}");
+                    assert_eq!(KnownType::from_id_tag(tag), tp);
+                }
+                None => assert!(!ID_TYPES.contains(&tp), "{tp:?}"),
+            }
+        }
     }
 }
@@ -0,0 +1,265 @@
+//! Strongly typed payloads of API-encoded strings. Each type accepts only strings with its own
+//! prefix and payloads of the size required by its [KnownType], so eg. a transaction hash cannot be
+//! passed where an account is expected. Types serialize as their encoded strings.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use super::*;
+
+macro_rules! impl_encoded {
+    ($name:ident, $tp:ident) => {
+        impl $name {
+            /// Type of the encoded payload.
+            pub const TYPE: KnownType = KnownType::$tp;
+
+            /// Wraps a payload. Fails if the size does not match the type.
+            pub fn new(bytes: Bytes) -> Result<Self, DecodingErr> {
+                check_type_size(Self::TYPE, bytes.len())?;
+                Ok($name(bytes))
+            }
+
+            /// Returns the payload.
+            pub fn as_bytes(&self) -> &[u8] {
+                &self.0
+            }
+
+            /// Unwraps the payload.
+            pub fn into_bytes(self) -> Bytes {
+                self.0
+            }
+        }
+
+        impl TryFrom<Bytes> for $name {
+            type Error = DecodingErr;
+
+            fn try_from(bytes: Bytes) -> Result<Self, DecodingErr> {
+                Self::new(bytes)
+            }
+        }
+
+        impl AsRef<[u8]> for $name {
+            fn as_ref(&self) -> &[u8] {
+                &self.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(&encode_data(Self::TYPE, &self.0))
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = DecodingErr;
+
+            fn from_str(s: &str) -> Result<Self, DecodingErr> {
+                let (tp, bytes) = decode(s)?;
+                if tp != Self::TYPE {
+                    Err(DecodingErr::InvalidPrefix)?;
+                }
+                Ok($name(bytes))
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_str(self)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let s = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+                s.parse().map_err(de::Error::custom)
+            }
+        }
+    };
+}
+
+macro_rules! impl_encoded_id {
+    ($name:ident) => {
+        impl TryFrom<$name> for id::Id {
+            type Error = DecodingErr;
+
+            /// Fails if the payload does not fit in an id.
+            fn try_from(x: $name) -> Result<Self, DecodingErr> {
+                let bytes = x.0.try_into().map_err(|_| DecodingErr::InvalidIdSize)?;
+                let tag = $name::TYPE.to_id_tag().expect("type without an id tag");
+                Ok(id::Id::new(tag, bytes))
+            }
+        }
+
+        impl TryFrom<id::Id> for $name {
+            type Error = DecodingErr;
+
+            /// Fails if the id is of another type.
+            fn try_from(id: id::Id) -> Result<Self, DecodingErr> {
+                if KnownType::from_id_tag(id.tag) != Self::TYPE {
+                    Err(DecodingErr::InvalidIdTag)?;
+                }
+                Self::new(id.val.as_bytes().to_vec())
+            }
+        }
+    };
+}
+
+macro_rules! encoded_types {
+    ($($(#[$attr:meta])* $name:ident => $tp:ident $(, $id:ident)?;)*) => {
+        $(
+            $(#[$attr])*
+            #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+            pub struct $name(Bytes);
+
+            impl_encoded!($name, $tp);
+            $(encoded_types!(@$id $name);)?
+        )*
+    };
+    (@id $name:ident) => {
+        impl_encoded_id!($name);
+    };
+}
+
+encoded_types! {
+    /// Hash of a key block (`kh_`).
+    KeyBlockHash => KeyBlockHash;
+    /// Hash of a micro block (`mh_`).
+    MicroBlockHash => MicroBlockHash;
+    /// Hash of a proof of fraud (`bf_`).
+    BlockPofHash => BlockPofHash;
+    /// Root hash of the transactions in a block (`bx_`).
+    BlockTxHash => BlockTxHash;
+    /// Root hash of the state trees (`bs_`).
+    BlockStateHash => BlockStateHash;
+    /// State channel id (`ch_`).
+    ChannelId => Channel, id;
+    /// Contract bytecode (`cb_`).
+    ContractBytearray => ContractBytearray;
+    /// Public key of a contract (`ct_`).
+    ContractPubkey => ContractPubkey, id;
+    /// Key in a contract store (`ck_`).
+    ContractStoreKey => ContractStoreKey;
+    /// Value in a contract store (`cv_`).
+    ContractStoreValue => ContractStoreValue;
+    /// Contract source code (`cx_`).
+    ContractSource => ContractSource;
+    /// Serialized transaction (`tx_`).
+    EncodedTx => Transaction;
+    /// Hash of a transaction (`th_`).
+    TxHash => TxHash;
+    /// Public key of an oracle (`ok_`).
+    OraclePubkey => OraclePubkey, id;
+    /// Oracle query (`ov_`).
+    OracleQuery => OracleQuery;
+    /// Id of an oracle query (`oq_`).
+    OracleQueryId => OracleQueryId;
+    /// Oracle response (`or_`).
+    OracleResponse => OracleResponse;
+    /// Public key of an account (`ak_`).
+    AccountPubkey => AccountPubkey, id;
+    /// Secret key of an account (`sk_`).
+    AccountSeckey => AccountSeckey;
+    /// Associated chain (`ac_`).
+    AssociateChain => AssociateChain, id;
+    /// Signature (`sg_`).
+    Signature => Signature;
+    /// Name (`nm_`).
+    Name => Name, id;
+    /// Native token (`an_`).
+    NativeToken => NativeToken, id;
+    /// Name preclaim commitment (`cm_`).
+    Commitment => Commitment, id;
+    /// Public key of a peer (`pp_`).
+    PeerPubkey => PeerPubkey;
+    /// State channel state (`st_`).
+    State => State;
+    /// Proof of inclusion (`pi_`).
+    Poi => Poi;
+    /// State trees (`ss_`).
+    StateTrees => StateTrees;
+    /// Call state tree (`cs_`).
+    CallStateTree => CallStateTree;
+    /// Arbitrary byte array (`ba_`).
+    Bytearray => Bytearray;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn string_roundtrip(bytes: [u8; 32]) {
+            let x = AccountPubkey::new(bytes.to_vec()).expect("wrapping failed");
+            let s = x.to_string();
+            prop_assert!(s.starts_with("ak_"));
+            prop_assert_eq!(s.parse::<AccountPubkey>(), Ok(x));
+        }
+
+        #[test]
+        fn unsized_roundtrip(bytes in prop::collection::vec(any::<u8>(), 0..256)) {
+            let x = EncodedTx::new(bytes.clone()).expect("wrapping failed");
+            prop_assert_eq!(x.as_ref(), &bytes[..]);
+            prop_assert_eq!(x.to_string().parse::<EncodedTx>(), Ok(x));
+        }
+
+        #[test]
+        fn id_roundtrip(bytes: [u8; 32]) {
+            let id = id::Id::new(id::Tag::Contract, bytes);
+            let x = ContractPubkey::try_from(id).expect("conversion failed");
+            prop_assert_eq!(x.as_bytes(), &bytes[..]);
+            prop_assert_eq!(id::Id::try_from(x), Ok(id));
+        }
+    }
+
+    #[test]
+    fn wrong_prefix() {
+        let hash = TxHash::new(vec![1; 32]).unwrap().to_string();
+        assert_eq!(hash.parse::<AccountPubkey>(), Err(DecodingErr::InvalidPrefix));
+        assert!(hash.parse::<TxHash>().is_ok());
+    }
+
+    #[test]
+    fn size_enforced() {
+        assert_eq!(
+            Signature::new(vec![1; 32]),
+            Err(DecodingErr::InvalidTypeSize {
+                tp: KnownType::Signature,
+                expected: 64,
+                actual: 32
+            })
+        );
+        assert!(Signature::new(vec![1; 64]).is_ok());
+
+        let encoded = encode_data(KnownType::AccountPubkey, &[1; 31]);
+        assert_eq!(
+            encoded.parse::<AccountPubkey>(),
+            Err(DecodingErr::InvalidTypeSize {
+                tp: KnownType::AccountPubkey,
+                expected: 32,
+                actual: 31
+            })
+        );
+    }
+
+    #[test]
+    fn id_tag_mismatch() {
+        let id = id::Id::new(id::Tag::Account, [1; 32]);
+        assert_eq!(OraclePubkey::try_from(id), Err(DecodingErr::InvalidIdTag));
+
+        let name = Name::new(b"foo.chain".to_vec()).unwrap();
+        assert_eq!(id::Id::try_from(name), Err(DecodingErr::InvalidIdSize));
+    }
+
+    #[test]
+    fn serde_string() {
+        let x = KeyBlockHash::new(vec![7; 32]).unwrap();
+        let json = serde_json::to_string(&x).unwrap();
+        assert_eq!(json, format!("\"{x}\""));
+        assert_eq!(serde_json::from_str::<KeyBlockHash>(&json).unwrap(), x);
+        assert!(serde_json::from_str::<MicroBlockHash>(&json).is_err());
+    }
+}
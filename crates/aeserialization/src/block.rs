@@ -0,0 +1,608 @@
+//! Block headers and micro block bodies. Unlike other chain objects, headers have a fixed binary
+//! layout instead of RLP. The hash of a serialized header is the hash of its block (`kh_` or `mh_`).
+
+use crate::api_encoder::{BlockPofHash, KeyBlockHash, MicroBlockHash};
+use crate::error::DecodingErr;
+use crate::rlp::{FromRlpItem, FromRlpRef, RlpItem, RlpRef, ToRlpItem};
+use crate::tx::SignedTx;
+use crate::{hash, Bytes};
+
+/// Identifier tag of a micro block body.
+pub const MICRO_BLOCK_TAG: u16 = 101;
+/// Identifier tag of a proof of fraud.
+pub const POF_TAG: u16 = 200;
+
+/// Number of cuckoo cycle nonces in a proof of work.
+pub const POW_SIZE: usize = 42;
+
+/// Size of a key header without the info field.
+pub const KEY_HEADER_SIZE: usize = 4 + 4 + 8 + 5 * 32 + 4 + POW_SIZE * 4 + 8 + 8;
+/// Size of a micro header without a proof of fraud hash.
+pub const MICRO_HEADER_SIZE: usize = 4 + 4 + 8 + 4 * 32 + 8 + 64;
+
+const KEY_FLAG: u32 = 1 << 31;
+// Marks the info field in key headers, and the proof of fraud hash in micro headers.
+const OPTIONAL_FIELD_FLAG: u32 = 1 << 30;
+
+/// Reads fixed-size fields of a header.
+struct Fields<'a>(&'a [u8]);
+
+impl Fields<'_> {
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], DecodingErr> {
+        let (field, rest) = self
+            .0
+            .split_first_chunk()
+            .ok_or(DecodingErr::InvalidHeader)?;
+        self.0 = rest;
+        Ok(*field)
+    }
+
+    fn u32(&mut self) -> Result<u32, DecodingErr> {
+        self.take().map(u32::from_be_bytes)
+    }
+
+    fn u64(&mut self) -> Result<u64, DecodingErr> {
+        self.take().map(u64::from_be_bytes)
+    }
+}
+
+/// Header of a key block, which carries the proof of work of the block leader.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyHeader {
+    /// Protocol version.
+    pub version: u32,
+    pub height: u64,
+    /// Hash of the previous block, either key or micro.
+    pub prev_hash: [u8; 32],
+    pub prev_key_hash: [u8; 32],
+    /// Root hash of the state trees (`bs_`).
+    pub root_hash: [u8; 32],
+    /// Public key of the leader, signing the following micro blocks.
+    pub miner: [u8; 32],
+    /// Public key of the account receiving the rewards.
+    pub beneficiary: [u8; 32],
+    /// Difficulty target in the compact format of Bitcoin.
+    pub target: u32,
+    /// Cuckoo cycle solution. Absent in the genesis block, where it is serialized as zeros.
+    pub pow: Option<[u32; POW_SIZE]>,
+    /// Nonce of the proof of work.
+    pub nonce: u64,
+    /// Milliseconds since the Unix epoch.
+    pub time: u64,
+    /// Miner signalling data, supported since the Minerva protocol.
+    pub info: Option<u32>,
+}
+
+impl KeyHeader {
+    pub fn serialize(&self) -> Bytes {
+        let mut out = Vec::with_capacity(KEY_HEADER_SIZE + 4);
+        let mut flags = KEY_FLAG;
+        if self.info.is_some() {
+            flags |= OPTIONAL_FIELD_FLAG;
+        }
+        out.extend_from_slice(&self.version.to_be_bytes());
+        out.extend_from_slice(&flags.to_be_bytes());
+        out.extend_from_slice(&self.height.to_be_bytes());
+        out.extend_from_slice(&self.prev_hash);
+        out.extend_from_slice(&self.prev_key_hash);
+        out.extend_from_slice(&self.root_hash);
+        out.extend_from_slice(&self.miner);
+        out.extend_from_slice(&self.beneficiary);
+        out.extend_from_slice(&self.target.to_be_bytes());
+        for n in self.pow.unwrap_or([0; POW_SIZE]) {
+            out.extend_from_slice(&n.to_be_bytes());
+        }
+        out.extend_from_slice(&self.nonce.to_be_bytes());
+        out.extend_from_slice(&self.time.to_be_bytes());
+        if let Some(info) = self.info {
+            out.extend_from_slice(&info.to_be_bytes());
+        }
+        out
+    }
+
+    pub fn deserialize(bytes: &[u8]) -> Result<Self, DecodingErr> {
+        let mut fields = Fields(bytes);
+        let version = fields.u32()?;
+        let flags = fields.u32()?;
+        let has_info = flags & OPTIONAL_FIELD_FLAG != 0;
+        let size = KEY_HEADER_SIZE + if has_info { 4 } else { 0 };
+        if flags & KEY_FLAG == 0 || bytes.len() != size {
+            Err(DecodingErr::InvalidHeader)?;
+        }
+
+        let mut header = KeyHeader {
+            version,
+            height: fields.u64()?,
+            prev_hash: fields.take()?,
+            prev_key_hash: fields.take()?,
+            root_hash: fields.take()?,
+            miner: fields.take()?,
+            beneficiary: fields.take()?,
+            target: fields.u32()?,
+            pow: None,
+            nonce: 0,
+            time: 0,
+            info: None,
+        };
+        let mut pow = [0; POW_SIZE];
+        for n in &mut pow {
+            *n = fields.u32()?;
+        }
+        header.pow = pow.iter().any(|n| *n != 0).then_some(pow);
+        header.nonce = fields.u64()?;
+        header.time = fields.u64()?;
+        if has_info {
+            header.info = Some(fields.u32()?);
+        }
+        Ok(header)
+    }
+
+    /// Hash of the key block.
+    pub fn hash(&self) -> KeyBlockHash {
+        KeyBlockHash::new(hash(&self.serialize()).to_vec()).expect("hash of an invalid size")
+    }
+}
+
+/// Header of a micro block, which carries transactions and is signed by the current leader.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MicroHeader {
+    /// Protocol version.
+    pub version: u32,
+    pub height: u64,
+    /// Hash of the previous block, either key or micro.
+    pub prev_hash: [u8; 32],
+    pub prev_key_hash: [u8; 32],
+    /// Root hash of the state trees (`bs_`).
+    pub root_hash: [u8; 32],
+    /// Root hash of the transactions of the block (`bx_`).
+    pub txs_hash: [u8; 32],
+    /// Milliseconds since the Unix epoch.
+    pub time: u64,
+    /// Hash of the proof of fraud of the block (`bf_`), if it reports one.
+    pub pof_hash: Option<[u8; 32]>,
+    /// Signature of the leader.
+    pub signature: [u8; 64],
+}
+
+impl MicroHeader {
+    pub fn serialize(&self) -> Bytes {
+        let mut out = Vec::with_capacity(MICRO_HEADER_SIZE + 32);
+        let flags = if self.pof_hash.is_some() {
+            OPTIONAL_FIELD_FLAG
+        } else {
+            0
+        };
+        out.extend_from_slice(&self.version.to_be_bytes());
+        out.extend_from_slice(&flags.to_be_bytes());
+        out.extend_from_slice(&self.height.to_be_bytes());
+        out.extend_from_slice(&self.prev_hash);
+        out.extend_from_slice(&self.prev_key_hash);
+        out.extend_from_slice(&self.root_hash);
+        out.extend_from_slice(&self.txs_hash);
+        out.extend_from_slice(&self.time.to_be_bytes());
+        if let Some(pof_hash) = &self.pof_hash {
+            out.extend_from_slice(pof_hash);
+        }
+        out.extend_from_slice(&self.signature);
+        out
+    }
+
+    pub fn deserialize(bytes: &[u8]) -> Result<Self, DecodingErr> {
+        let mut fields = Fields(bytes);
+        let version = fields.u32()?;
+        let flags = fields.u32()?;
+        let has_pof = flags & OPTIONAL_FIELD_FLAG != 0;
+        if flags & KEY_FLAG != 0 || bytes.len() != Self::size(has_pof) {
+            Err(DecodingErr::InvalidHeader)?;
+        }
+
+        Ok(MicroHeader {
+            version,
+            height: fields.u64()?,
+            prev_hash: fields.take()?,
+            prev_key_hash: fields.take()?,
+            root_hash: fields.take()?,
+            txs_hash: fields.take()?,
+            time: fields.u64()?,
+            pof_hash: if has_pof { Some(fields.take()?) } else { None },
+            signature: fields.take()?,
+        })
+    }
+
+    /// Size of a serialized header, depending on whether it holds a proof of fraud hash.
+    fn size(has_pof: bool) -> usize {
+        MICRO_HEADER_SIZE + if has_pof { 32 } else { 0 }
+    }
+
+    /// Hash of the micro block. It covers the signature as well.
+    pub fn hash(&self) -> MicroBlockHash {
+        MicroBlockHash::new(hash(&self.serialize()).to_vec()).expect("hash of an invalid size")
+    }
+}
+
+/// Header of either kind of block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Header {
+    Key(KeyHeader),
+    Micro(MicroHeader),
+}
+
+impl Header {
+    pub fn serialize(&self) -> Bytes {
+        match self {
+            Header::Key(h) => h.serialize(),
+            Header::Micro(h) => h.serialize(),
+        }
+    }
+
+    /// Deserializes a header of the kind given by its flags.
+    pub fn deserialize(bytes: &[u8]) -> Result<Self, DecodingErr> {
+        let flags = Fields(bytes.get(4..).unwrap_or_default()).u32()?;
+        if flags & KEY_FLAG != 0 {
+            KeyHeader::deserialize(bytes).map(Header::Key)
+        } else {
+            MicroHeader::deserialize(bytes).map(Header::Micro)
+        }
+    }
+
+    /// Raw hash of the block.
+    pub fn hash(&self) -> [u8; 32] {
+        hash(&self.serialize())
+    }
+
+    pub fn height(&self) -> u64 {
+        match self {
+            Header::Key(h) => h.height,
+            Header::Micro(h) => h.height,
+        }
+    }
+
+    pub fn prev_hash(&self) -> &[u8; 32] {
+        match self {
+            Header::Key(h) => &h.prev_hash,
+            Header::Micro(h) => &h.prev_hash,
+        }
+    }
+
+    pub fn prev_key_hash(&self) -> &[u8; 32] {
+        match self {
+            Header::Key(h) => &h.prev_key_hash,
+            Header::Micro(h) => &h.prev_key_hash,
+        }
+    }
+}
+
+// Headers nested in RLP objects are byte arrays of their binary serialization.
+macro_rules! impl_rlp_header {
+    ($($t:ty),*) => {$(
+        impl ToRlpItem for $t {
+            fn to_rlp_item(&self) -> RlpItem {
+                RlpItem::ByteArray(self.serialize())
+            }
+        }
+
+        impl FromRlpItem for $t {
+            fn from_rlp_item(item: &RlpItem) -> Result<Self, DecodingErr> {
+                <$t>::deserialize(item.as_bytes()?)
+            }
+        }
+
+        impl<'a> FromRlpRef<'a> for $t {
+            fn from_rlp_ref(item: &RlpRef<'a>) -> Result<Self, DecodingErr> {
+                <$t>::deserialize(item.byte_array()?)
+            }
+        }
+    )*};
+}
+
+impl_rlp_header!(KeyHeader, MicroHeader, Header);
+
+/// Proof that a leader signed two micro blocks at the same height, forking the chain.
+#[derive(Debug, Clone, PartialEq, ToRlpItem, FromRlpItem, FromRlpRef)]
+#[rlp(tag = POF_TAG, vsn = 1u8)]
+pub struct Pof {
+    pub header1: MicroHeader,
+    pub header2: MicroHeader,
+    /// Public key of the fraudulent leader.
+    pub pubkey: [u8; 32],
+}
+
+impl Pof {
+    /// Hash committed to in the header of the reporting micro block.
+    pub fn hash(&self) -> BlockPofHash {
+        BlockPofHash::new(hash(&self.serialize_rlp()).to_vec()).expect("hash of an invalid size")
+    }
+}
+
+/// Micro block, serialized as its header followed by the RLP encoded body. The body is versioned
+/// along with the header.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MicroBlock {
+    pub header: MicroHeader,
+    pub txs: Vec<SignedTx>,
+    pub pof: Option<Pof>,
+}
+
+impl MicroBlock {
+    pub fn serialize(&self) -> Bytes {
+        let mut out = self.header.serialize();
+        let as_bytes = |x: &dyn ToRlpItem| RlpItem::ByteArray(x.serialize_rlp());
+        let body = RlpItem::List(vec![
+            MICRO_BLOCK_TAG.to_rlp_item(),
+            self.header.version.to_rlp_item(),
+            RlpItem::List(self.txs.iter().map(|tx| as_bytes(tx)).collect()),
+            RlpItem::List(self.pof.iter().map(|pof| as_bytes(pof)).collect()),
+        ]);
+        body.serialize_into(&mut out);
+        out
+    }
+
+    pub fn deserialize(bytes: &[u8]) -> Result<Self, DecodingErr> {
+        let flags = Fields(bytes.get(4..).unwrap_or_default()).u32()?;
+        let header_size = MicroHeader::size(flags & OPTIONAL_FIELD_FLAG != 0);
+        if bytes.len() < header_size {
+            Err(DecodingErr::InvalidHeader)?;
+        }
+        let (header, body) = bytes.split_at(header_size);
+        let header = MicroHeader::deserialize(header)?;
+
+        let body = RlpRef::deserialize(body)?;
+        let invalid = || DecodingErr::InvalidObject {
+            object: "MicroBlock",
+            fields: 4,
+        };
+        let [tag, vsn, txs, pof] = body.list()?.collect::<Result<Vec<_>, _>>()?[..] else {
+            Err(invalid())?
+        };
+        if u16::from_rlp_ref(&tag) != Ok(MICRO_BLOCK_TAG) {
+            Err(DecodingErr::InvalidTag {
+                object: "MicroBlock",
+            })?;
+        }
+        if u32::from_rlp_ref(&vsn) != Ok(header.version) {
+            Err(DecodingErr::InvalidVersion {
+                object: "MicroBlock",
+            })?;
+        }
+
+        let txs = txs
+            .list()?
+            .map(|tx| SignedTx::deserialize_rlp_ref(tx?.byte_array()?))
+            .collect::<Result<_, _>>()?;
+        let pofs = pof
+            .list()?
+            .map(|pof| Pof::deserialize_rlp_ref(pof?.byte_array()?))
+            .collect::<Result<Vec<_>, _>>()?;
+        if pofs.len() > 1 {
+            Err(invalid())?;
+        }
+
+        Ok(MicroBlock {
+            header,
+            txs,
+            pof: pofs.into_iter().next(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn key_header() -> KeyHeader {
+        KeyHeader {
+            version: 5,
+            height: 100,
+            prev_hash: [1; 32],
+            prev_key_hash: [2; 32],
+            root_hash: [3; 32],
+            miner: [4; 32],
+            beneficiary: [5; 32],
+            target: 0x1d00ffff,
+            pow: Some([7; POW_SIZE]),
+            nonce: 42,
+            time: 1_700_000_000_000,
+            info: None,
+        }
+    }
+
+    fn micro_header() -> MicroHeader {
+        MicroHeader {
+            version: 5,
+            height: 100,
+            prev_hash: [1; 32],
+            prev_key_hash: [2; 32],
+            root_hash: [3; 32],
+            txs_hash: [4; 32],
+            time: 1_700_000_000_000,
+            pof_hash: None,
+            signature: [5; 64],
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn key_header_roundtrip(
+            height: u64,
+            hashes: [[u8; 32]; 5],
+            pow: Option<u32>,
+            nonce: u64,
+            info: Option<u32>,
+        ) {
+            let header = KeyHeader {
+                height,
+                prev_hash: hashes[0],
+                prev_key_hash: hashes[1],
+                root_hash: hashes[2],
+                miner: hashes[3],
+                beneficiary: hashes[4],
+                pow: pow.map(|n| [n | 1; POW_SIZE]),
+                nonce,
+                info,
+                ..key_header()
+            };
+            let bytes = header.serialize();
+            prop_assert_eq!(KeyHeader::deserialize(&bytes), Ok(header.clone()));
+            prop_assert_eq!(Header::deserialize(&bytes), Ok(Header::Key(header)));
+        }
+
+        #[test]
+        fn micro_header_roundtrip(
+            height: u64,
+            hashes: [[u8; 32]; 4],
+            pof_hash: Option<[u8; 32]>,
+            time: u64,
+        ) {
+            let header = MicroHeader {
+                height,
+                prev_hash: hashes[0],
+                prev_key_hash: hashes[1],
+                root_hash: hashes[2],
+                txs_hash: hashes[3],
+                pof_hash,
+                time,
+                ..micro_header()
+            };
+            let bytes = header.serialize();
+            prop_assert_eq!(MicroHeader::deserialize(&bytes), Ok(header.clone()));
+            prop_assert_eq!(Header::deserialize(&bytes), Ok(Header::Micro(header)));
+        }
+    }
+
+    #[test]
+    fn key_header_layout() {
+        let mut header = key_header();
+        let bytes = header.serialize();
+        assert_eq!(bytes.len(), KEY_HEADER_SIZE);
+        assert_eq!(bytes.len(), 364);
+        assert_eq!(
+            &bytes[..16],
+            &[0, 0, 0, 5, 0x80, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 100]
+        );
+        assert_eq!(&bytes[176..180], &[0x1d, 0x00, 0xff, 0xff]);
+        assert_eq!(&bytes[180..184], &[0, 0, 0, 7]);
+
+        header.info = Some(0x1234);
+        header.pow = None;
+        let bytes = header.serialize();
+        assert_eq!(bytes.len(), 368);
+        assert_eq!(bytes[4], 0xc0);
+        assert!(bytes[180..348].iter().all(|b| *b == 0));
+        assert_eq!(&bytes[364..], &[0, 0, 0x12, 0x34]);
+        assert_eq!(KeyHeader::deserialize(&bytes), Ok(header));
+    }
+
+    #[test]
+    fn micro_header_layout() {
+        let mut header = micro_header();
+        let bytes = header.serialize();
+        assert_eq!(bytes.len(), 216);
+        assert_eq!(&bytes[4..8], &[0, 0, 0, 0]);
+        assert_eq!(&bytes[152..216], &[5; 64]);
+
+        header.pof_hash = Some([6; 32]);
+        let bytes = header.serialize();
+        assert_eq!(bytes.len(), 248);
+        assert_eq!(&bytes[4..8], &[0x40, 0, 0, 0]);
+        assert_eq!(&bytes[152..184], &[6; 32]);
+    }
+
+    #[test]
+    fn header_hashes() {
+        use blake2::{digest::consts::U32, Blake2b, Digest};
+
+        let key = key_header();
+        let expect: [u8; 32] = Blake2b::<U32>::digest(key.serialize()).into();
+        assert_eq!(key.hash().as_bytes(), &expect[..]);
+        assert!(key.hash().to_string().starts_with("kh_"));
+        assert_eq!(Header::Key(key).hash(), expect);
+
+        let micro = micro_header();
+        assert!(micro.hash().to_string().starts_with("mh_"));
+        assert_eq!(micro.hash().as_bytes(), &Header::Micro(micro).hash()[..]);
+    }
+
+    #[test]
+    fn invalid_headers() {
+        let key = key_header().serialize();
+        let micro = micro_header().serialize();
+        assert_eq!(
+            MicroHeader::deserialize(&key),
+            Err(DecodingErr::InvalidHeader)
+        );
+        assert_eq!(
+            KeyHeader::deserialize(&micro),
+            Err(DecodingErr::InvalidHeader)
+        );
+        assert_eq!(
+            KeyHeader::deserialize(&key[..363]),
+            Err(DecodingErr::InvalidHeader)
+        );
+        assert_eq!(
+            MicroHeader::deserialize(&[&micro[..], &[0]].concat()),
+            Err(DecodingErr::InvalidHeader)
+        );
+        assert_eq!(
+            Header::deserialize(&[0; 6]),
+            Err(DecodingErr::InvalidHeader)
+        );
+    }
+
+    #[test]
+    fn micro_block_roundtrip() {
+        let mut signed = SignedTx::new(vec![1; 20]);
+        signed.signatures.push(vec![2; 64]);
+        let pof = Pof {
+            header1: micro_header(),
+            header2: MicroHeader {
+                txs_hash: [9; 32],
+                ..micro_header()
+            },
+            pubkey: [4; 32],
+        };
+        let block = MicroBlock {
+            header: MicroHeader {
+                pof_hash: Some(pof.hash().as_bytes().try_into().unwrap()),
+                ..micro_header()
+            },
+            txs: vec![signed.clone(), signed],
+            pof: Some(pof),
+        };
+        let bytes = block.serialize();
+        assert_eq!(MicroBlock::deserialize(&bytes), Ok(block.clone()));
+
+        let empty = MicroBlock {
+            header: micro_header(),
+            txs: vec![],
+            pof: None,
+        };
+        let bytes = empty.serialize();
+        let body = RlpItem::deserialize(&bytes[MICRO_HEADER_SIZE..]).unwrap();
+        let expect = RlpItem::List(vec![
+            RlpItem::ByteArray(vec![101]),
+            RlpItem::ByteArray(vec![5]),
+            RlpItem::List(vec![]),
+            RlpItem::List(vec![]),
+        ]);
+        assert_eq!(body, expect);
+        assert_eq!(MicroBlock::deserialize(&bytes), Ok(empty));
+    }
+
+    #[test]
+    fn micro_block_version_mismatch() {
+        let block = MicroBlock {
+            header: micro_header(),
+            txs: vec![],
+            pof: None,
+        };
+        let mut bytes = block.serialize();
+        bytes[3] = 6;
+        assert_eq!(
+            MicroBlock::deserialize(&bytes),
+            Err(DecodingErr::InvalidVersion {
+                object: "MicroBlock"
+            })
+        );
+    }
+}
@@ -1,5 +1,5 @@
 use crate::error::DecodingErr;
-use crate::rlp::{FromRlpItem, RlpItem, ToRlpItem};
+use crate::rlp::{FromRlpItem, FromRlpRef, ToRlpItem};
 use crate::Bytes;
 
 // TODO: this should come from another module which has not been rewritten yet
@@ -9,7 +9,7 @@ pub const CODE_TAG: u8 = 70;
 /// Contract format version.
 pub const VSN: u8 = 3;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, ToRlpItem, FromRlpItem, FromRlpRef)]
 pub struct TypeInfo {
     pub type_hash: Bytes,
     pub name: Bytes,
@@ -18,66 +18,25 @@ pub struct TypeInfo {
     pub out_type: Bytes,
 }
 
-impl ToRlpItem for TypeInfo {
-    fn to_rlp_item(&self) -> RlpItem {
-        RlpItem::List(vec![
-            RlpItem::ByteArray(self.type_hash.clone()),
-            RlpItem::ByteArray(self.name.clone()),
-            self.payable.to_rlp_item(),
-            RlpItem::ByteArray(self.arg_type.clone()),
-            RlpItem::ByteArray(self.out_type.clone()),
-        ])
-    }
-}
-
-impl ToRlpItem for Vec<TypeInfo> {
-    fn to_rlp_item(&self) -> RlpItem {
-        RlpItem::List(self.iter().map(|x| x.to_rlp_item()).collect())
-    }
-}
-
-impl FromRlpItem for TypeInfo {
-    fn from_rlp_item(item: &RlpItem) -> Result<Self, DecodingErr> {
-        let items = item.list().map_err(|_| DecodingErr::InvalidRlp)?;
-
-        if items.len() != 5 {
-            Err(DecodingErr::InvalidRlp)?;
-        }
-
-        Ok(TypeInfo {
-            type_hash: items[0].byte_array()?,
-            name: items[1].byte_array()?,
-            payable: bool::from_rlp_item(&items[2])?,
-            arg_type: items[3].byte_array()?,
-            out_type: items[4].byte_array()?,
-        })
-    }
-}
-
-impl FromRlpItem for Vec<TypeInfo> {
-    fn from_rlp_item(item: &RlpItem) -> Result<Self, DecodingErr> {
-        let rlps = item.list()?;
-        rlps.into_iter().map(|x| TypeInfo::from_rlp_item(&x)).collect()
-    }
-}
-
-/// FATE contract code with metadata
-#[derive(Debug, PartialEq)]
+/// FATE contract code with metadata. Fields are laid out in the order of serialization. Code is
+/// always serialized with version [VSN], while any version is accepted when decoding.
+#[derive(Debug, PartialEq, ToRlpItem, FromRlpItem, FromRlpRef)]
+#[rlp(tag = CODE_TAG, vsn = VSN, any_vsn)]
 pub struct Code {
-    /// Byte code of the contract.
-    pub byte_code: Bytes,
-    /// Whether the contract can receive tokens through a `spend` transaction.
-    pub payable: bool,
     /// Hash of the source code in the original smart contract language. Note that verification of
     /// this field is not imposed by the æternity protocol, thus its validity has to always be
     /// checked before the contract is used.
     pub source_hash: Bytes,
+    /// AEVM residue. Kept for compatibility.
+    pub type_info: Vec<TypeInfo>,
+    /// Byte code of the contract.
+    pub byte_code: Bytes,
     /// Version of the compiler of the original smart contract language. Note that verification of
     /// this field is not imposed by the æternity protocol, thus its validity has to always be
     /// checked before the contract is used.
     pub compiler_version: Bytes,
-    /// AEVM residue. Kept for compatibility.
-    pub type_info: Vec<TypeInfo>,
+    /// Whether the contract can receive tokens through a `spend` transaction.
+    pub payable: bool,
 }
 
 impl Code {
@@ -92,64 +51,17 @@ impl Code {
     }
 }
 
-impl ToRlpItem for Code {
-    fn to_rlp_item(&self) -> RlpItem {
-        let fields = vec![
-            // Tag
-            CODE_TAG.to_rlp_item(),
-            // Contract version
-            VSN.to_rlp_item(),
-            // Source hash
-            RlpItem::ByteArray(self.source_hash.to_vec()),
-            // Type info (generally useless, AEVM residue)
-            self.type_info.to_rlp_item(),
-            // Byte code
-            RlpItem::ByteArray(self.byte_code.to_vec()),
-            // Contract version
-            RlpItem::ByteArray(self.compiler_version.to_vec()),
-            // Payable
-            self.payable.to_rlp_item(),
-        ];
-        RlpItem::List(fields)
-    }
-}
-
-impl FromRlpItem for Code {
-    fn from_rlp_item(item: &RlpItem) -> Result<Self, DecodingErr> {
-        let items = item.list().map_err(|_| DecodingErr::InvalidRlp)?;
-
-        if items.len() != 7 {
-            Err(DecodingErr::InvalidRlp)?;
-        }
-
-        if u8::from_rlp_item(&items[0])? != CODE_TAG {
-            Err(DecodingErr::InvalidRlp)?;
-        }
-
-        Ok(Code {
-            source_hash: items[2].byte_array()?,
-            type_info: Vec::<TypeInfo>::from_rlp_item(&items[3])?,
-            byte_code: items[4].byte_array()?,
-            compiler_version: items[5].byte_array()?,
-            payable: bool::from_rlp_item(&items[6])?,
-        })
-    }
-}
-
 /// A universal function to hash original contract source code. Note that verifying the hash is not
 /// imposed by the æternity protocol, thus its validity has to always be checked before the contract
 /// is used.
 pub fn hash_source_code(str: &str) -> Bytes {
-    use blake2::{digest::consts::U32, Blake2b, Digest};
-    type Blake2b32 = Blake2b<U32>;
-    let mut hasher = Blake2b32::new();
-    hasher.update(str);
-    hasher.finalize().to_vec()
+    crate::hash(str.as_bytes()).to_vec()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::rlp::RlpItem;
 
     #[test]
     fn source_hash() {
@@ -218,5 +130,57 @@ mod tests {
 
         assert_eq!(serialized, expect);
         assert_eq!(deserialized, Ok(input));
+        assert_eq!(Code::deserialize_rlp_ref(&expect), deserialized);
+    }
+
+    #[test]
+    fn sophia_contract_version3_textual() {
+        let expect: RlpItem = r#"
+            [
+                70,       # tag
+                3,        # version
+                0x303a7dedbc2c78d5349b5c04d5089decc6a1f009755b3ca7402c435291aeeef3,
+                [],       # type info
+                "DUMMY_CODE",
+                "3.1.4",  # compiler version
+                1,        # payable
+            ]
+        "#
+        .parse()
+        .expect("invalid fixture");
+
+        let code = Code::from_rlp_item(&expect).expect("decoding failed");
+
+        assert_eq!(code.byte_code, b"DUMMY_CODE");
+        assert_eq!(code.source_hash, hash_source_code("contract Foo = ..."));
+        assert_eq!(code.serialize(), expect.serialize());
+    }
+
+    #[test]
+    fn any_code_version() {
+        let mut item = Code {
+            byte_code: b"DUMMY_CODE".to_vec(),
+            source_hash: hash_source_code("contract Foo = ..."),
+            compiler_version: b"3.1.4".to_vec(),
+            payable: false,
+            type_info: vec![],
+        }
+        .to_rlp_item();
+        if let RlpItem::List(items) = &mut item {
+            items[1] = RlpItem::ByteArray(vec![1]);
+        }
+
+        // Older code versions are decoded as well, but re-encoded with the current one.
+        let code = Code::from_rlp_item(&item).expect("decoding failed");
+        assert_eq!(Code::deserialize_rlp_ref(&item.serialize()).as_ref(), Ok(&code));
+        assert_eq!(code.to_rlp_item().get::<u8>(1), Ok(VSN));
+
+        if let RlpItem::List(items) = &mut item {
+            items[0] = RlpItem::ByteArray(vec![71]);
+        }
+        assert_eq!(
+            Code::from_rlp_item(&item),
+            Err(DecodingErr::InvalidTag { object: "Code" })
+        );
     }
 }
@@ -1,3 +1,8 @@
+use std::fmt;
+
+use crate::api_encoder::KnownType;
+use crate::rlp;
+
 /// Error type for aeser operations.
 #[derive(Debug, PartialEq)]
 pub enum DecodingErr {
@@ -16,17 +21,129 @@ pub enum DecodingErr {
     /// RLP item is not a recursive list.
     InvalidList,
     /// Malformed RLP item.
-    InvalidRlp,
+    InvalidRlp(rlp::DecodingErr),
     /// Invalid object type prefix.
     InvalidPrefix,
     /// Object type prefix is not included.
     MissingPrefix,
     /// The object size does not match the one implied by the prefix.
     IncorrectSize,
+    /// The payload size does not match the one required by its type.
+    InvalidTypeSize {
+        tp: KnownType,
+        expected: usize,
+        actual: usize,
+    },
     /// Failure in decoding payload of an object (eg. malformed base64).
     InvalidEncoding,
+    /// Character outside of the alphabet of the encoding, at the given position of the input.
+    InvalidCharacter { position: usize },
+    /// Decoded payload is too short to hold a checksum.
+    MissingCheck,
     /// Checksum does not match.
-    InvalidCheck,
+    InvalidCheck { expected: [u8; 4], actual: [u8; 4] },
     /// Malformed contract code.
     InvalidCode,
+    /// Block header of an unexpected size or kind.
+    InvalidHeader,
+    /// Malformed Merkle Patricia tree node.
+    InvalidNode,
+    /// Hash does not match the hashed object.
+    InvalidHash,
+    /// Object is not an RLP list with the expected number of fields.
+    InvalidObject {
+        object: &'static str,
+        fields: usize,
+    },
+    /// Object tag does not match the expected one.
+    InvalidTag { object: &'static str },
+    /// Object version is not supported.
+    InvalidVersion { object: &'static str },
+    /// Failed decoding a field of an object.
+    InvalidField {
+        object: &'static str,
+        field: &'static str,
+        err: Box<DecodingErr>,
+    },
+    /// Failed accessing or decoding the item at the given path of list indices.
+    InvalidPath {
+        path: Vec<usize>,
+        err: Box<DecodingErr>,
+    },
+    /// List has no element at the given index.
+    IndexOutOfBounds { index: usize, len: usize },
+}
+
+impl fmt::Display for DecodingErr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodingErr::InvalidIdSize => f.write_str("invalid id size"),
+            DecodingErr::InvalidIdTag => f.write_str("invalid id tag"),
+            DecodingErr::InvalidIdPub => f.write_str("invalid id payload"),
+            DecodingErr::InvalidBool => f.write_str("expected a bool encoded as 0 or 1"),
+            DecodingErr::InvalidInt => {
+                f.write_str("expected an integer encoded as minimal big-endian bytes")
+            }
+            DecodingErr::InvalidBinary => f.write_str("expected an RLP byte array"),
+            DecodingErr::InvalidList => f.write_str("expected an RLP list"),
+            DecodingErr::InvalidRlp(_) => f.write_str("malformed RLP"),
+            DecodingErr::InvalidPrefix => f.write_str("invalid object type prefix"),
+            DecodingErr::MissingPrefix => f.write_str("missing object type prefix"),
+            DecodingErr::IncorrectSize => f.write_str("object size does not match its type"),
+            DecodingErr::InvalidTypeSize {
+                tp,
+                expected,
+                actual,
+            } => write!(f, "{tp:?} takes {expected} bytes, but {actual} were given"),
+            DecodingErr::InvalidEncoding => f.write_str("malformed object payload"),
+            DecodingErr::InvalidCharacter { position } => {
+                write!(f, "invalid character at position {position}")
+            }
+            DecodingErr::MissingCheck => f.write_str("payload is too short to hold a checksum"),
+            DecodingErr::InvalidCheck { expected, actual } => write!(
+                f,
+                "checksum mismatch: expected {}, found {}",
+                hex(expected),
+                hex(actual)
+            ),
+            DecodingErr::InvalidCode => f.write_str("malformed contract code"),
+            DecodingErr::InvalidHeader => f.write_str("malformed block header"),
+            DecodingErr::InvalidNode => f.write_str("malformed Merkle Patricia tree node"),
+            DecodingErr::InvalidHash => f.write_str("hash does not match the hashed object"),
+            DecodingErr::InvalidObject { object, fields } => {
+                write!(f, "expected {object} as an RLP list of {fields} fields")
+            }
+            DecodingErr::InvalidTag { object } => write!(f, "invalid tag of {object}"),
+            DecodingErr::InvalidVersion { object } => write!(f, "unsupported version of {object}"),
+            DecodingErr::InvalidField { object, field, .. } => {
+                write!(f, "invalid field `{field}` of {object}")
+            }
+            DecodingErr::InvalidPath { path, .. } => write!(f, "invalid item at path {path:?}"),
+            DecodingErr::IndexOutOfBounds { index, len } => {
+                write!(f, "no element at index {index} of a list of {len}")
+            }
+        }
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+impl std::error::Error for DecodingErr {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DecodingErr::InvalidRlp(err) => Some(err),
+            DecodingErr::InvalidField { err, .. } | DecodingErr::InvalidPath { err, .. } => {
+                Some(err.as_ref())
+            }
+            _ => None,
+        }
+    }
+}
+
+impl From<rlp::DecodingErr> for DecodingErr {
+    fn from(err: rlp::DecodingErr) -> Self {
+        DecodingErr::InvalidRlp(err)
+    }
 }
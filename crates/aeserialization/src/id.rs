@@ -1,4 +1,7 @@
-use crate::{error::DecodingErr, rlp::{RlpItem, ToRlpItem, FromRlpItem}, Bytes};
+use crate::{api_encoder, error::DecodingErr, rlp::{RlpItem, RlpRef, ToRlpItem, FromRlpItem, FromRlpRef}, Bytes};
+
+use std::fmt;
+use std::str::FromStr;
 
 use num::{FromPrimitive, ToPrimitive};
 use num_derive::{FromPrimitive, ToPrimitive};
@@ -12,7 +15,7 @@ pub const TAG_SIZE: usize = 1;
 pub const SERIALIZED_SIZE: usize = TAG_SIZE + PUB_SIZE;
 
 /// Denotes the type of an id.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, FromPrimitive, ToPrimitive, TS)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, FromPrimitive, ToPrimitive, TS)]
 #[ts(export)]
 pub enum Tag {
     Account = 1,
@@ -20,15 +23,95 @@ pub enum Tag {
     Commitment = 3,
     Oracle = 4,
     Contract = 5,
-    Channel = 6
+    Channel = 6,
+    AssociateChain = 7,
+    NativeToken = 8,
+}
+
+impl Tag {
+    /// Lowercase name of the tag.
+    pub fn name(self) -> &'static str {
+        match self {
+            Tag::Account => "account",
+            Tag::Name => "name",
+            Tag::Commitment => "commitment",
+            Tag::Oracle => "oracle",
+            Tag::Contract => "contract",
+            Tag::Channel => "channel",
+            Tag::AssociateChain => "associate_chain",
+            Tag::NativeToken => "native_token",
+        }
+    }
+}
+
+impl fmt::Display for Tag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Tag {
+    type Err = DecodingErr;
+
+    /// Parses a tag from its name. See [Tag::name].
+    fn from_str(s: &str) -> Result<Self, DecodingErr> {
+        match s {
+            "account" => Ok(Tag::Account),
+            "name" => Ok(Tag::Name),
+            "commitment" => Ok(Tag::Commitment),
+            "oracle" => Ok(Tag::Oracle),
+            "contract" => Ok(Tag::Contract),
+            "channel" => Ok(Tag::Channel),
+            "associate_chain" => Ok(Tag::AssociateChain),
+            "native_token" => Ok(Tag::NativeToken),
+            _ => Err(DecodingErr::InvalidIdTag),
+        }
+    }
 }
 
 /// Wrapper for an id payload.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, TS)]
-pub struct EncodedId { pub bytes: [u8; PUB_SIZE] } // TODO: hermetize
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, TS)]
+pub struct EncodedId { bytes: [u8; PUB_SIZE] }
+
+impl EncodedId {
+    pub fn new(bytes: [u8; PUB_SIZE]) -> Self {
+        EncodedId { bytes }
+    }
+
+    /// Returns the payload.
+    pub fn as_bytes(&self) -> &[u8; PUB_SIZE] {
+        &self.bytes
+    }
+
+    /// Unwraps the payload.
+    pub fn into_bytes(self) -> [u8; PUB_SIZE] {
+        self.bytes
+    }
+}
+
+impl From<[u8; PUB_SIZE]> for EncodedId {
+    fn from(bytes: [u8; PUB_SIZE]) -> Self {
+        EncodedId::new(bytes)
+    }
+}
+
+impl TryFrom<&[u8]> for EncodedId {
+    type Error = DecodingErr;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, DecodingErr> {
+        let bytes = bytes.try_into().map_err(|_| DecodingErr::InvalidIdPub)?;
+        Ok(EncodedId::new(bytes))
+    }
+}
+
+impl AsRef<[u8]> for EncodedId {
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
 
-/// Identifier of a chain object.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, TS)]
+/// Identifier of a chain object. Ids are ordered by their tag first, and then by their payload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, TS)]
 #[ts(export)]
 pub struct Id {
     pub tag: Tag,
@@ -36,6 +119,10 @@ pub struct Id {
 }
 
 impl Id {
+    pub fn new(tag: Tag, bytes: [u8; PUB_SIZE]) -> Self {
+        Id { tag, val: EncodedId::new(bytes) }
+    }
+
     /// Serializes an id into a byte array.
     pub fn serialize(&self) -> Bytes {
         let mut encoded: Bytes = vec![0; 33];
@@ -51,11 +138,100 @@ impl Id {
         }
 
         let tag: Tag = Tag::from_u8(bytes[0]).ok_or(DecodingErr::InvalidIdTag)?;
-        let val: [u8; 32] = bytes[TAG_SIZE..].try_into().or(Err(DecodingErr::InvalidIdPub))?;
-        Ok(Id {tag, val: EncodedId{bytes: val}})
+        let val = EncodedId::try_from(&bytes[TAG_SIZE..])?;
+        Ok(Id {tag, val})
+    }
+}
+
+impl fmt::Display for Id {
+    /// Formats the id in the API encoding, eg. `ak_...`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&api_encoder::encode_id(self))
+    }
+}
+
+impl FromStr for Id {
+    type Err = DecodingErr;
+
+    /// Parses an API-encoded id of any type.
+    fn from_str(s: &str) -> Result<Self, DecodingErr> {
+        api_encoder::decode_id(api_encoder::ID_TYPES, s)
     }
 }
 
+impl serde::Serialize for Id {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Id {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Size of nonces included in derived ids.
+const NONCE_SIZE: usize = 32;
+
+fn hash(parts: &[&[u8]]) -> [u8; PUB_SIZE] {
+    crate::hash(&parts.concat())
+}
+
+fn nonce_bytes(nonce: u64) -> [u8; NONCE_SIZE] {
+    let mut bytes = [0; NONCE_SIZE];
+    bytes[NONCE_SIZE - 8..].copy_from_slice(&nonce.to_be_bytes());
+    bytes
+}
+
+/// Id of a contract created by `owner` in a transaction with the given nonce.
+pub fn contract_id(owner: &EncodedId, nonce: u64) -> Id {
+    // Unlike in other ids, the nonce is minimally encoded, with 0 being a single zero byte.
+    let nonce = nonce.to_be_bytes();
+    let skip = nonce.iter().take_while(|b| **b == 0).count().min(nonce.len() - 1);
+    Id::new(Tag::Contract, hash(&[&owner.bytes, &nonce[skip..]]))
+}
+
+/// Id of an oracle query posted by `sender` in a transaction with the given nonce. Query ids are
+/// not tagged, so they are returned in their API-encoded form.
+pub fn oracle_query_id(
+    sender: &EncodedId,
+    nonce: u64,
+    oracle: &EncodedId,
+) -> api_encoder::OracleQueryId {
+    let bytes = hash(&[&sender.bytes, &nonce_bytes(nonce), &oracle.bytes]);
+    api_encoder::OracleQueryId::new(bytes.to_vec()).expect("query id of an invalid size")
+}
+
+/// AENS hash of a name: the hash of its ASCII form, as used since the Lima protocol. The name has
+/// to be normalized beforehand.
+pub fn name_hash(name: &str) -> [u8; PUB_SIZE] {
+    hash(&[name.as_bytes()])
+}
+
+/// Id of an AENS name. See [name_hash].
+pub fn name_id(name: &str) -> Id {
+    Id::new(Tag::Name, name_hash(name))
+}
+
+/// Id of a commitment to claim a name, hiding it with a salt: the hash of the name followed by the
+/// salt as a 32-byte big-endian integer. The salt has to fit in 32 bytes.
+pub fn commitment_id(name: &str, salt: &num::BigUint) -> Result<Id, DecodingErr> {
+    let salt = salt.to_bytes_be();
+    if salt.len() > PUB_SIZE {
+        Err(DecodingErr::IncorrectSize)?;
+    }
+    let mut salt_bytes = [0; PUB_SIZE];
+    salt_bytes[PUB_SIZE - salt.len()..].copy_from_slice(&salt);
+    Ok(Id::new(Tag::Commitment, hash(&[name.as_bytes(), &salt_bytes])))
+}
+
+/// Id of a state channel opened by `initiator` in a transaction with the given nonce.
+pub fn channel_id(initiator: &EncodedId, nonce: u64, responder: &EncodedId) -> Id {
+    Id::new(Tag::Channel, hash(&[&initiator.bytes, &nonce_bytes(nonce), &responder.bytes]))
+}
+
 impl ToRlpItem for Id {
     fn to_rlp_item(&self) -> RlpItem {
         let encoded = self.serialize();
@@ -66,7 +242,7 @@ impl ToRlpItem for Id {
 impl FromRlpItem for Id {
     fn from_rlp_item(item: &RlpItem) -> Result<Self, DecodingErr> {
         match item {
-            RlpItem::List(_) => Err(DecodingErr::InvalidRlp),
+            RlpItem::List(_) => Err(DecodingErr::InvalidBinary),
             RlpItem::ByteArray(bytes) => {
                 Id::deserialize(bytes)
             }
@@ -74,11 +250,27 @@ impl FromRlpItem for Id {
     }
 }
 
+impl<'a> FromRlpRef<'a> for Id {
+    fn from_rlp_ref(item: &RlpRef<'a>) -> Result<Self, DecodingErr> {
+        match item {
+            RlpRef::List(_) => Err(DecodingErr::InvalidBinary),
+            RlpRef::ByteArray(bytes) => {
+                Id::deserialize(bytes)
+            }
+        }
+    }
+}
+
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
     use proptest::prelude::*;
 
+    /// Account id with all bytes set to `n`.
+    pub(crate) fn account(n: u8) -> Id {
+        Id::new(Tag::Account, [n; PUB_SIZE])
+    }
+
     impl proptest::arbitrary::Arbitrary for Tag {
         type Parameters = ();
         type Strategy = BoxedStrategy<Self>;
@@ -90,6 +282,8 @@ mod tests {
                 Just(Tag::Oracle),
                 Just(Tag::Contract),
                 Just(Tag::Channel),
+                Just(Tag::AssociateChain),
+                Just(Tag::NativeToken),
             ].boxed()
         }
     }
@@ -99,7 +293,7 @@ mod tests {
         type Strategy = BoxedStrategy<Self>;
         fn arbitrary_with(_args: Self::Parameters) -> Self::Strategy {
             (any::<Tag>(), any::<[u8; 32]>())
-                .prop_map(|(t, v)| Id{tag: t, val: EncodedId{bytes: v}})
+                .prop_map(|(t, v)| Id::new(t, v))
                 .boxed()
         }
     }
@@ -111,6 +305,98 @@ mod tests {
             let id1: Id = FromRlpItem::from_rlp_item(&rlp).expect("Decoding id from rlp");
             prop_assert_eq!(id1, id);
         }
+
+        #[test]
+        fn id_rlp_ref_roundtrip(id: Id) {
+            let encoded = id.serialize_rlp();
+            let id1 = Id::deserialize_rlp_ref(&encoded).expect("Decoding id from rlp ref");
+            prop_assert_eq!(id1, id);
+        }
+
+        #[test]
+        fn id_string_roundtrip(id: Id) {
+            let s = id.to_string();
+            prop_assert_eq!(s.parse::<Id>(), Ok(id));
+        }
+
+        #[test]
+        fn id_serde_roundtrip(id: Id) {
+            let json = serde_json::to_string(&id).expect("Serializing id");
+            prop_assert_eq!(&json, &format!("\"{id}\""));
+            let id1: Id = serde_json::from_str(&json).expect("Deserializing id");
+            prop_assert_eq!(id1, id);
+        }
+
+        #[test]
+        fn tag_string_roundtrip(tag: Tag) {
+            prop_assert_eq!(tag.to_string().parse::<Tag>(), Ok(tag));
+        }
+    }
+
+    #[test]
+    fn id_from_str() {
+        let id = Id::new(Tag::Contract, [3; PUB_SIZE]);
+        assert!(id.to_string().starts_with("ct_"));
+
+        let hash = api_encoder::encode_data(api_encoder::KnownType::TxHash, &[3; PUB_SIZE]);
+        assert_eq!(hash.parse::<Id>(), Err(DecodingErr::InvalidPrefix));
+    }
+
+    // The expected ids below are not taken from the node's test suites. They were computed with
+    // Python's hashlib and a separate base58check encoder, following
+    // `aect_contracts:compute_contract_pubkey/2`, `aesc_channels:pubkey/3`, `aeo_query:id/3` and
+    // `aens_hash:name_hash/1` and `commitment_hash/2`.
+    fn id(s: &str) -> Id {
+        s.parse().unwrap()
     }
 
+    #[test]
+    fn contract_ids() {
+        let owner = id("ak_SeLqn3UAUoRymWmwW7axrzJK7JfNaBR2cHCryA6cFsgFkHEF").val;
+        assert_eq!(owner, EncodedId::new([1; PUB_SIZE]));
+
+        let expect = id("ct_2gb2t8mgBUtjwohCFVM13UH4Zxgc3TGGcVDLRS3qTArMrMm5K8");
+        assert_eq!(contract_id(&owner, 0), expect);
+        let expect = id("ct_2Fy7ATRCjsAn7kTZ27eW5RosdCdCUewgmQZ18x2mckACXE1uG");
+        assert_eq!(contract_id(&owner, 256), expect);
+    }
+
+    #[test]
+    fn nonce_padded_ids() {
+        let a = EncodedId::new([1; PUB_SIZE]);
+        let b = EncodedId::new([2; PUB_SIZE]);
+
+        let expect = id("ch_2iazTzgJ18G6nRqyjyLHBCB6GGRuwXzZKQgFhYrdKVVUCwBq1R");
+        assert_eq!(channel_id(&a, 7, &b), expect);
+        assert_eq!(oracle_query_id(&a, 7, &b).as_bytes(), &expect.val.bytes[..]);
+        assert_ne!(channel_id(&b, 7, &a), channel_id(&a, 7, &b));
+    }
+
+    #[test]
+    fn name_ids() {
+        let expect = id("nm_24x1du7V43adefJbR81xha3JLwshxDTTmgJ6JqCTjmb5hDHpDh");
+        assert_eq!(name_id("foo.chain"), expect);
+        assert_eq!(name_hash("foo.chain"), expect.val.bytes);
+
+        let salt = num::BigUint::from(0x0102u32);
+        let expect = id("cm_2Y36Mh7Ny8C46fy859doJE2Db4x3JSUgaZiycTdtqc8uzh41Cy");
+        assert_eq!(commitment_id("foo.chain", &salt), Ok(expect));
+
+        let huge_salt = num::BigUint::from(1u32) << 256;
+        assert_eq!(commitment_id("foo.chain", &huge_salt), Err(DecodingErr::IncorrectSize));
+    }
+
+    #[test]
+    fn id_ordering() {
+        use std::collections::{BTreeSet, HashSet};
+
+        let ids = [
+            Id::new(Tag::Contract, [1; PUB_SIZE]),
+            Id::new(Tag::Account, [2; PUB_SIZE]),
+            Id::new(Tag::Account, [1; PUB_SIZE]),
+        ];
+        let sorted: Vec<Id> = ids.iter().copied().collect::<BTreeSet<_>>().into_iter().collect();
+        assert_eq!(sorted, vec![ids[2], ids[1], ids[0]]);
+        assert_eq!(ids.iter().collect::<HashSet<_>>().len(), 3);
+    }
 }
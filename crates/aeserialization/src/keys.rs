@@ -0,0 +1,294 @@
+//! Ed25519 account keys. Secret keys are 32-byte seeds encoded as `sk_`, public keys are encoded as
+//! `ak_`, and signatures as `sg_`.
+
+use std::fmt;
+
+use ed25519_dalek::{Signer, SigningKey, Verifier, VerifyingKey};
+use rand_core::CryptoRngCore;
+
+use crate::api_encoder::{AccountPubkey, AccountSeckey, Signature};
+use crate::id;
+
+pub mod hd;
+
+/// An error while verifying a signature.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyErr {
+    /// Public key is not a valid curve point.
+    InvalidPublicKey,
+    /// Id does not refer to an account.
+    NotAnAccount,
+    /// Signature does not match the message and the key.
+    InvalidSignature,
+}
+
+impl fmt::Display for KeyErr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeyErr::InvalidPublicKey => f.write_str("invalid ed25519 public key"),
+            KeyErr::NotAnAccount => f.write_str("id does not refer to an account"),
+            KeyErr::InvalidSignature => f.write_str("signature verification failed"),
+        }
+    }
+}
+
+impl std::error::Error for KeyErr {}
+
+/// Account keypair. The secret key is not exposed through [fmt::Debug].
+#[derive(Clone)]
+pub struct KeyPair {
+    signing: SigningKey,
+}
+
+impl KeyPair {
+    /// Creates a keypair from a 32-byte secret seed.
+    pub fn from_seed(seed: &[u8; 32]) -> Self {
+        KeyPair {
+            signing: SigningKey::from_bytes(seed),
+        }
+    }
+
+    /// Creates a keypair from an `sk_` secret key.
+    pub fn from_secret_key(sk: &AccountSeckey) -> Self {
+        let seed = sk
+            .as_bytes()
+            .try_into()
+            .expect("secret key of an invalid size");
+        Self::from_seed(seed)
+    }
+
+    /// Generates a new keypair.
+    pub fn generate<R: CryptoRngCore + ?Sized>(rng: &mut R) -> Self {
+        KeyPair {
+            signing: SigningKey::generate(rng),
+        }
+    }
+
+    pub fn secret_key(&self) -> AccountSeckey {
+        AccountSeckey::new(self.signing.to_bytes().to_vec()).expect("secret key of an invalid size")
+    }
+
+    pub fn public_key(&self) -> AccountPubkey {
+        let bytes = self.signing.verifying_key().to_bytes();
+        AccountPubkey::new(bytes.to_vec()).expect("public key of an invalid size")
+    }
+
+    /// Account id of the public key.
+    pub fn id(&self) -> id::Id {
+        id::Id::new(id::Tag::Account, self.signing.verifying_key().to_bytes())
+    }
+
+    /// Signs arbitrary bytes.
+    pub fn sign(&self, msg: &[u8]) -> Signature {
+        let sig = self.signing.sign(msg).to_bytes();
+        Signature::new(sig.to_vec()).expect("signature of an invalid size")
+    }
+}
+
+impl fmt::Debug for KeyPair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeyPair")
+            .field("public_key", &self.public_key().to_string())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Prefix of signed personal messages, distinguishing them from transactions.
+pub const MESSAGE_PREFIX: &[u8] = b"aeternity Signed Message:\n";
+
+/// Appends a length in the variable-length format of Bitcoin (compact size).
+fn push_varuint(out: &mut Vec<u8>, n: usize) {
+    let n = n as u64;
+    match n {
+        0..=0xfc => out.push(n as u8),
+        0xfd..=0xffff => {
+            out.push(0xfd);
+            out.extend_from_slice(&(n as u16).to_le_bytes());
+        }
+        0x10000..=0xffff_ffff => {
+            out.push(0xfe);
+            out.extend_from_slice(&(n as u32).to_le_bytes());
+        }
+        _ => {
+            out.push(0xff);
+            out.extend_from_slice(&n.to_le_bytes());
+        }
+    }
+}
+
+/// Hash of a personal message, as signed by wallets. Both the prefix and the message are preceded
+/// by their lengths.
+pub fn message_hash(msg: &[u8]) -> [u8; 32] {
+    let mut data = Vec::with_capacity(MESSAGE_PREFIX.len() + msg.len() + 10);
+    push_varuint(&mut data, MESSAGE_PREFIX.len());
+    data.extend_from_slice(MESSAGE_PREFIX);
+    push_varuint(&mut data, msg.len());
+    data.extend_from_slice(msg);
+    crate::hash(&data)
+}
+
+/// Signs a personal message. See [message_hash].
+pub fn sign_message(keypair: &KeyPair, msg: &[u8]) -> Signature {
+    keypair.sign(&message_hash(msg))
+}
+
+/// Verifies a signature of a personal message. See [message_hash].
+pub fn verify_message(pk: &AccountPubkey, msg: &[u8], sig: &Signature) -> Result<(), KeyErr> {
+    verify(pk, &message_hash(msg), sig)
+}
+
+/// Derives the public key of a secret key.
+pub fn public_key(sk: &AccountSeckey) -> AccountPubkey {
+    KeyPair::from_secret_key(sk).public_key()
+}
+
+/// Verifies a signature of arbitrary bytes.
+pub fn verify(pk: &AccountPubkey, msg: &[u8], sig: &Signature) -> Result<(), KeyErr> {
+    let pk: &[u8; 32] = pk
+        .as_bytes()
+        .try_into()
+        .expect("public key of an invalid size");
+    let key = VerifyingKey::from_bytes(pk).map_err(|_| KeyErr::InvalidPublicKey)?;
+    let sig = ed25519_dalek::Signature::from_slice(sig.as_bytes())
+        .map_err(|_| KeyErr::InvalidSignature)?;
+    key.verify(msg, &sig).map_err(|_| KeyErr::InvalidSignature)
+}
+
+/// Verifies a signature of arbitrary bytes made by an account.
+pub fn verify_id(id: &id::Id, msg: &[u8], sig: &Signature) -> Result<(), KeyErr> {
+    let pk = AccountPubkey::try_from(*id).map_err(|_| KeyErr::NotAnAccount)?;
+    verify(&pk, msg, sig)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn sign_verify(seed: [u8; 32], msg in prop::collection::vec(any::<u8>(), 0..64)) {
+            let kp = KeyPair::from_seed(&seed);
+            let sig = kp.sign(&msg);
+            prop_assert_eq!(verify(&kp.public_key(), &msg, &sig), Ok(()));
+            prop_assert_eq!(verify_id(&kp.id(), &msg, &sig), Ok(()));
+
+            let other = [&msg[..], b"x"].concat();
+            prop_assert_eq!(verify(&kp.public_key(), &other, &sig), Err(KeyErr::InvalidSignature));
+        }
+
+        #[test]
+        fn encoded_keys(seed: [u8; 32]) {
+            let kp = KeyPair::from_seed(&seed);
+            let sk: AccountSeckey = kp.secret_key().to_string().parse().expect("parsing sk failed");
+            prop_assert_eq!(public_key(&sk), kp.public_key());
+            prop_assert_eq!(id::Id::try_from(kp.public_key()), Ok(kp.id()));
+        }
+    }
+
+    #[test]
+    fn rfc8032_vector() {
+        // Test 2 from RFC 8032, section 7.1.
+        let seed = [
+            0x4c, 0xcd, 0x08, 0x9b, 0x28, 0xff, 0x96, 0xda, 0x9d, 0xb6, 0xc3, 0x46, 0xec, 0x11,
+            0x4e, 0x0f, 0x5b, 0x8a, 0x31, 0x9f, 0x35, 0xab, 0xa6, 0x24, 0xda, 0x8c, 0xf6, 0xed,
+            0x4f, 0xb8, 0xa6, 0xfb,
+        ];
+        let pk = [
+            0x3d, 0x40, 0x17, 0xc3, 0xe8, 0x43, 0x89, 0x5a, 0x92, 0xb7, 0x0a, 0xa7, 0x4d, 0x1b,
+            0x7e, 0xbc, 0x9c, 0x98, 0x2c, 0xcf, 0x2e, 0xc4, 0x96, 0x8c, 0xc0, 0xcd, 0x55, 0xf1,
+            0x2a, 0xf4, 0x66, 0x0c,
+        ];
+        let sig = [
+            0x92, 0xa0, 0x09, 0xa9, 0xf0, 0xd4, 0xca, 0xb8, 0x72, 0x0e, 0x82, 0x0b, 0x5f, 0x64,
+            0x25, 0x40, 0xa2, 0xb2, 0x7b, 0x54, 0x16, 0x50, 0x3f, 0x8f, 0xb3, 0x76, 0x22, 0x23,
+            0xeb, 0xdb, 0x69, 0xda, 0x08, 0x5a, 0xc1, 0xe4, 0x3e, 0x15, 0x99, 0x6e, 0x45, 0x8f,
+            0x36, 0x13, 0xd0, 0xf1, 0x1d, 0x8c, 0x38, 0x7b, 0x2e, 0xae, 0xb4, 0x30, 0x2a, 0xee,
+            0xb0, 0x0d, 0x29, 0x16, 0x12, 0xbb, 0x0c, 0x00,
+        ];
+
+        let kp = KeyPair::from_seed(&seed);
+        assert_eq!(kp.public_key().as_bytes(), &pk[..]);
+        assert_eq!(kp.sign(&[0x72]).as_bytes(), &sig[..]);
+    }
+
+    #[test]
+    fn generated_keys() {
+        let kp = KeyPair::generate(&mut rand_core::OsRng);
+        let sig = kp.sign(b"hello");
+        assert_eq!(verify(&kp.public_key(), b"hello", &sig), Ok(()));
+        assert!(!format!("{kp:?}").contains(&kp.secret_key().to_string()));
+    }
+
+    #[test]
+    fn message_hashes() {
+        use blake2::{digest::consts::U32, Blake2b, Digest};
+
+        let data = [&[26][..], b"aeternity Signed Message:\n", &[5], b"hello"].concat();
+        let expect: [u8; 32] = Blake2b::<U32>::digest(&data).into();
+        assert_eq!(message_hash(b"hello"), expect);
+
+        let long = vec![b'a'; 300];
+        let data = [&[26][..], MESSAGE_PREFIX, &[0xfd, 0x2c, 0x01], &long].concat();
+        let expect: [u8; 32] = Blake2b::<U32>::digest(&data).into();
+        assert_eq!(message_hash(&long), expect);
+    }
+
+    #[test]
+    fn varuint_lengths() {
+        let encode = |n| {
+            let mut out = Vec::new();
+            push_varuint(&mut out, n);
+            out
+        };
+        assert_eq!(encode(0xfc), vec![0xfc]);
+        assert_eq!(encode(0xfd), vec![0xfd, 0xfd, 0x00]);
+        assert_eq!(encode(0x10000), vec![0xfe, 0x00, 0x00, 0x01, 0x00]);
+    }
+
+    #[test]
+    fn signed_messages() {
+        let kp = KeyPair::from_seed(&[5; 32]);
+        let sig = sign_message(&kp, b"login");
+        assert_eq!(verify_message(&kp.public_key(), b"login", &sig), Ok(()));
+        assert_eq!(verify_message(&kp.public_key(), b"logout", &sig), Err(KeyErr::InvalidSignature));
+        // Messages are not interchangeable with raw signed data.
+        assert!(verify(&kp.public_key(), b"login", &sig).is_err());
+    }
+
+    #[test]
+    fn signed_message_vector() {
+        // Key pair from the crypto unit tests of aepp-sdk-js. The hash and the signature of the
+        // message are not SDK fixtures: they were computed with Python's hashlib and Ed25519
+        // implementation, following `messageToHash` of the SDK.
+        let seed = [
+            0x4d, 0x88, 0x1d, 0xd1, 0x91, 0x70, 0x36, 0xcc, 0x23, 0x1f, 0x98, 0x81, 0xa0, 0xdb,
+            0x97, 0x8c, 0x88, 0x99, 0xdd, 0x76, 0xa8, 0x17, 0x25, 0x24, 0x18, 0x60, 0x6b, 0x02,
+            0xbf, 0x6a, 0xb9, 0xd2,
+        ];
+        let hash = [
+            0x8e, 0x7a, 0x65, 0xa5, 0xee, 0x17, 0xc1, 0x7d, 0x50, 0x22, 0x57, 0xc0, 0xfa, 0xa1,
+            0x18, 0x71, 0x74, 0x70, 0x3e, 0x67, 0x82, 0x9c, 0x18, 0x7f, 0xe0, 0x89, 0x1f, 0x3c,
+            0x82, 0x63, 0x91, 0x60,
+        ];
+        let kp = KeyPair::from_seed(&seed);
+        let pk: AccountPubkey = "ak_Gd6iMVsoonGuTF8LeswwDDN2NF5wYHAoTRtzwdEcfS32LWoxm".parse().unwrap();
+        assert_eq!(kp.public_key(), pk);
+
+        let msg = b"test";
+        assert_eq!(message_hash(msg), hash);
+        let sig: Signature = "sg_TJhUpWC3baRMpKfkkYxZtuKAnPPQcHTHjB9mL8faymxtDpdsbRnrRgXbJY2Ruj8AZ9QxofsRNdV1x2f6KsRrKSgFTyG37"
+            .parse()
+            .unwrap();
+        assert_eq!(sign_message(&kp, msg), sig);
+        assert_eq!(verify_message(&pk, msg, &sig), Ok(()));
+    }
+
+    #[test]
+    fn non_account_id() {
+        let kp = KeyPair::from_seed(&[1; 32]);
+        let sig = kp.sign(b"hello");
+        let id = id::Id::new(id::Tag::Contract, *kp.id().val.as_bytes());
+        assert_eq!(verify_id(&id, b"hello", &sig), Err(KeyErr::NotAnAccount));
+    }
+}
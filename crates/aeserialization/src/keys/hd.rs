@@ -0,0 +1,372 @@
+//! Hierarchical deterministic derivation of account keys, following SLIP-0010 for ed25519 along
+//! the aeternity path `m/44'/457'/n'/0'/0'`. Seeds can be obtained from BIP-39 mnemonics, checked
+//! against the bundled English wordlist.
+
+use std::fmt;
+
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256, Sha512};
+use unicode_normalization::UnicodeNormalization;
+
+use super::KeyPair;
+use crate::api_encoder::{AccountPubkey, AccountSeckey};
+
+/// The BIP-39 English wordlist, sorted.
+const WORDLIST: &str = include_str!("english.txt");
+
+/// Offset of hardened child indices. Only hardened derivation is defined for ed25519.
+pub const HARDENED: u32 = 1 << 31;
+
+/// BIP-44 coin type of aeternity.
+pub const COIN_TYPE: u32 = 457;
+
+/// An error while handling a mnemonic or a derivation path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HdErr {
+    /// Mnemonic does not consist of 12, 15, 18, 21 or 24 words.
+    InvalidWordCount(usize),
+    /// Word at the given position is not in the wordlist.
+    UnknownWord { position: usize },
+    /// Mnemonic checksum does not match.
+    InvalidChecksum,
+    /// Entropy size is not a multiple of 4 bytes between 16 and 32.
+    InvalidEntropy,
+    /// Malformed derivation path.
+    InvalidPath,
+    /// Derivation path includes a non-hardened index.
+    NonHardened { index: u32 },
+}
+
+impl fmt::Display for HdErr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HdErr::InvalidWordCount(n) => write!(f, "mnemonic of {n} words"),
+            HdErr::UnknownWord { position } => write!(f, "unknown word at position {position}"),
+            HdErr::InvalidChecksum => f.write_str("mnemonic checksum mismatch"),
+            HdErr::InvalidEntropy => f.write_str("invalid entropy size"),
+            HdErr::InvalidPath => f.write_str("malformed derivation path"),
+            HdErr::NonHardened { index } => {
+                write!(f, "index {index} is not hardened, as required by ed25519")
+            }
+        }
+    }
+}
+
+impl std::error::Error for HdErr {}
+
+fn words() -> impl Iterator<Item = &'static str> {
+    WORDLIST.lines()
+}
+
+fn word_index(word: &str) -> Option<usize> {
+    // The wordlist is small enough for the lookup to not matter next to PBKDF2.
+    words().position(|w| w == word)
+}
+
+/// Encodes entropy as a mnemonic.
+pub fn entropy_to_mnemonic(entropy: &[u8]) -> Result<String, HdErr> {
+    if !(16..=32).contains(&entropy.len()) || !entropy.len().is_multiple_of(4) {
+        Err(HdErr::InvalidEntropy)?;
+    }
+
+    let check = Sha256::digest(entropy);
+    let bits = entropy.len() * 8 + entropy.len() / 4;
+    let bit = |i: usize| {
+        let byte = if i < entropy.len() * 8 {
+            entropy[i / 8]
+        } else {
+            check[i / 8 - entropy.len()]
+        };
+        (byte >> (7 - i % 8)) & 1
+    };
+
+    let wordlist: Vec<&str> = words().collect();
+    let mnemonic: Vec<&str> = (0..bits / 11)
+        .map(|w| {
+            let idx = (0..11).fold(0, |acc, i| (acc << 1) | bit(w * 11 + i) as usize);
+            wordlist[idx]
+        })
+        .collect();
+    Ok(mnemonic.join(" "))
+}
+
+/// Verifies the words and the checksum of a mnemonic. Returns the entropy.
+pub fn mnemonic_to_entropy(mnemonic: &str) -> Result<Vec<u8>, HdErr> {
+    let mnemonic: String = mnemonic.nfkd().collect();
+    let indices = mnemonic
+        .split_whitespace()
+        .enumerate()
+        .map(|(position, w)| word_index(w).ok_or(HdErr::UnknownWord { position }))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if ![12, 15, 18, 21, 24].contains(&indices.len()) {
+        Err(HdErr::InvalidWordCount(indices.len()))?;
+    }
+
+    let mut bits = indices
+        .iter()
+        .flat_map(|idx| (0..11).rev().map(move |i| (idx >> i) & 1 == 1));
+    let entropy_len = indices.len() * 11 * 32 / 33 / 8;
+    let entropy: Vec<u8> = (0..entropy_len)
+        .map(|_| (0..8).fold(0, |acc, _| (acc << 1) | bits.next().unwrap() as u8))
+        .collect();
+    let check: u8 = bits.fold(0, |acc, b| (acc << 1) | b as u8);
+
+    let check_bits = entropy_len / 4;
+    if Sha256::digest(&entropy)[0] >> (8 - check_bits) != check {
+        Err(HdErr::InvalidChecksum)?;
+    }
+
+    Ok(entropy)
+}
+
+/// Converts a mnemonic to a seed, after verifying it. The passphrase may be empty.
+pub fn mnemonic_to_seed(mnemonic: &str, passphrase: &str) -> Result<[u8; 64], HdErr> {
+    mnemonic_to_entropy(mnemonic)?;
+
+    let mnemonic: String = mnemonic.nfkd().collect();
+    let mnemonic = mnemonic.split_whitespace().collect::<Vec<_>>().join(" ");
+    let salt: String = format!("mnemonic{passphrase}").nfkd().collect();
+
+    let mut seed = [0; 64];
+    pbkdf2::pbkdf2_hmac::<Sha512>(mnemonic.as_bytes(), salt.as_bytes(), 2048, &mut seed);
+    Ok(seed)
+}
+
+/// Secret key along with the chain code used to derive its children.
+#[derive(Clone)]
+pub struct ExtendedKey {
+    key: [u8; 32],
+    chain_code: [u8; 32],
+}
+
+impl ExtendedKey {
+    /// Derives the master key from a seed.
+    pub fn master(seed: &[u8]) -> Self {
+        Self::from_hmac(b"ed25519 seed", &[seed])
+    }
+
+    fn from_hmac(key: &[u8], data: &[&[u8]]) -> Self {
+        let mut mac = Hmac::<Sha512>::new_from_slice(key).expect("HMAC accepts keys of any size");
+        for part in data {
+            mac.update(part);
+        }
+        let out = mac.finalize().into_bytes();
+        ExtendedKey {
+            key: out[..32].try_into().expect("digest too short"),
+            chain_code: out[32..].try_into().expect("digest too short"),
+        }
+    }
+
+    /// Derives a child key. The index has to be hardened, see [HARDENED].
+    pub fn derive(&self, index: u32) -> Result<Self, HdErr> {
+        if index < HARDENED {
+            Err(HdErr::NonHardened { index })?;
+        }
+        Ok(Self::from_hmac(
+            &self.chain_code,
+            &[&[0], &self.key, &index.to_be_bytes()],
+        ))
+    }
+
+    /// Derives a key along a path such as `m/44'/457'/0'/0'/0'`. All indices have to be hardened.
+    pub fn derive_path(&self, path: &str) -> Result<Self, HdErr> {
+        let mut parts = path.split('/');
+        if parts.next() != Some("m") {
+            Err(HdErr::InvalidPath)?;
+        }
+        parts.try_fold(self.clone(), |key, part| {
+            let index = match part.strip_suffix('\'').or_else(|| part.strip_suffix('H')) {
+                Some(index) => {
+                    index
+                        .parse::<u32>()
+                        .ok()
+                        .filter(|x| *x < HARDENED)
+                        .ok_or(HdErr::InvalidPath)?
+                        + HARDENED
+                }
+                None => part.parse().map_err(|_| HdErr::InvalidPath)?,
+            };
+            key.derive(index)
+        })
+    }
+
+    pub fn secret_key(&self) -> &[u8; 32] {
+        &self.key
+    }
+
+    pub fn chain_code(&self) -> &[u8; 32] {
+        &self.chain_code
+    }
+
+    pub fn keypair(&self) -> KeyPair {
+        KeyPair::from_seed(&self.key)
+    }
+}
+
+impl fmt::Debug for ExtendedKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExtendedKey").finish_non_exhaustive()
+    }
+}
+
+/// Derives the keypair of the account at the given index, along `m/44'/457'/index'/0'/0'`.
+pub fn account_keypair(seed: &[u8], index: u32) -> Result<KeyPair, HdErr> {
+    if index >= HARDENED {
+        Err(HdErr::InvalidPath)?;
+    }
+    [
+        44 + HARDENED,
+        COIN_TYPE + HARDENED,
+        index + HARDENED,
+        HARDENED,
+        HARDENED,
+    ]
+    .iter()
+    .try_fold(ExtendedKey::master(seed), |key, i| key.derive(*i))
+    .map(|key| key.keypair())
+}
+
+/// Derives the encoded keys of the account at the given index. See [account_keypair].
+pub fn account_keys(seed: &[u8], index: u32) -> Result<(AccountSeckey, AccountPubkey), HdErr> {
+    let keypair = account_keypair(seed, index)?;
+    Ok((keypair.secret_key(), keypair.public_key()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn hex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    proptest! {
+        #[test]
+        fn mnemonic_roundtrip(entropy in prop::collection::vec(any::<u8>(), 16..=32)) {
+            let entropy = &entropy[..entropy.len() / 4 * 4];
+            let mnemonic = entropy_to_mnemonic(entropy).expect("encoding failed");
+            prop_assert_eq!(mnemonic_to_entropy(&mnemonic).expect("decoding failed"), entropy);
+        }
+    }
+
+    #[test]
+    fn wordlist() {
+        let words: Vec<_> = words().collect();
+        assert_eq!(words.len(), 2048);
+        assert!(words.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn bip39_vector() {
+        // From the reference BIP-39 test vectors, using the passphrase "TREZOR".
+        let mnemonic = "abandon abandon abandon abandon abandon abandon abandon abandon abandon \
+                        abandon abandon about";
+        assert_eq!(entropy_to_mnemonic(&[0; 16]).unwrap(), mnemonic);
+        let seed = mnemonic_to_seed(mnemonic, "TREZOR").unwrap();
+        assert_eq!(
+            seed.to_vec(),
+            hex("c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04")
+        );
+    }
+
+    #[test]
+    fn invalid_mnemonics() {
+        let words = "abandon ".repeat(11);
+        assert_eq!(
+            mnemonic_to_entropy(&format!("{words}abandon")),
+            Err(HdErr::InvalidChecksum)
+        );
+        assert_eq!(
+            mnemonic_to_entropy(&format!("{words}aeternity")),
+            Err(HdErr::UnknownWord { position: 11 })
+        );
+        assert_eq!(
+            mnemonic_to_entropy(&words),
+            Err(HdErr::InvalidWordCount(11))
+        );
+    }
+
+    #[test]
+    fn slip10_vector() {
+        // Test vector 1 for ed25519 from SLIP-0010, with the chain code and secret key of each key
+        // along the path m/0'/1'/2'/2'/1000000000'.
+        let chain = [
+            (
+                None,
+                "90046a93de5380a72b5e45010748567d5ea02bbf6522f979e05c0d8d8ca9fffb",
+                "2b4be7f19ee27bbf30c667b642d5f4aa69fd169872f8fc3059c08ebae2eb19e7",
+            ),
+            (
+                Some(0),
+                "8b59aa11380b624e81507a27fedda59fea6d0b779a778918a2fd3590e16e9c69",
+                "68e0fe46dfb67e368c75379acec591dad19df3cde26e63b93a8e704f1dade7a3",
+            ),
+            (
+                Some(1),
+                "a320425f77d1b5c2505a6b1b27382b37368ee640e3557c315416801243552f14",
+                "b1d0bad404bf35da785a64ca1ac54b2617211d2777696fbffaf208f746ae84f2",
+            ),
+            (
+                Some(2),
+                "2e69929e00b5ab250f49c3fb1c12f252de4fed2c1db88387094a0f8c4c9ccd6c",
+                "92a5b23c0b8a99e37d07df3fb9966917f5d06e02ddbd909c7e184371463e9fc9",
+            ),
+            (
+                Some(2),
+                "8f6d87f93d750e0efccda017d662a1b31a266e4a6f5993b15f5c1f07f74dd5cc",
+                "30d1dc7e5fc04c31219ab25a27ae00b50f6fd66622f6e9c913253d6511d1e662",
+            ),
+            (
+                Some(1000000000),
+                "68789923a0cac2cd5a29172a475fe9e0fb14cd6adb5ad98a3fa70333e7afa230",
+                "8f94d394a8e8fd6b1bc2f3f49f5c47e385281d5c17e65324b0f62483e37e8793",
+            ),
+        ];
+
+        let mut key = ExtendedKey::master(&hex("000102030405060708090a0b0c0d0e0f"));
+        for (index, chain_code, secret_key) in chain {
+            if let Some(index) = index {
+                key = key.derive(index + HARDENED).unwrap();
+            }
+            assert_eq!(key.chain_code().to_vec(), hex(chain_code));
+            assert_eq!(key.secret_key().to_vec(), hex(secret_key));
+        }
+
+        let by_path = ExtendedKey::master(&hex("000102030405060708090a0b0c0d0e0f"))
+            .derive_path("m/0'/1'/2'/2'/1000000000'")
+            .unwrap();
+        assert_eq!(by_path.secret_key(), key.secret_key());
+    }
+
+    #[test]
+    fn account_vector() {
+        // Account at m/44'/457'/0'/0'/0' of the BIP-39 test mnemonic, without a passphrase.
+        let mnemonic = "abandon abandon abandon abandon abandon abandon abandon abandon abandon \
+                        abandon abandon about";
+        let seed = mnemonic_to_seed(mnemonic, "").unwrap();
+        let (_, pk) = account_keys(&seed, 0).unwrap();
+        assert_eq!(pk.to_string(), "ak_21SBPc3yHP7bpQDvD1KMKzZZEgLtSXpDsK97LTjVwjiskra6Ka");
+    }
+
+    #[test]
+    fn paths() {
+        let master = ExtendedKey::master(&[1; 64]);
+        let by_path = master.derive_path("m/44'/457'/3'/0'/0'").unwrap();
+        let (sk, pk) = account_keys(&[1; 64], 3).unwrap();
+        assert_eq!(by_path.secret_key().as_slice(), sk.as_bytes());
+        assert_eq!(by_path.keypair().public_key(), pk);
+        assert_ne!(account_keys(&[1; 64], 4).unwrap().1, pk);
+
+        assert_eq!(
+            master.derive_path("m/44'/1").unwrap_err(),
+            HdErr::NonHardened { index: 1 }
+        );
+        assert_eq!(master.derive_path("44'").unwrap_err(), HdErr::InvalidPath);
+        assert_eq!(master.derive_path("m/x'").unwrap_err(), HdErr::InvalidPath);
+    }
+}
@@ -1,8 +1,16 @@
+// Lets the derive macros refer to `::aeser` from within this crate as well.
+extern crate self as aeser;
+
 pub mod id;
 pub mod contract_code;
 pub mod rlp;
 pub mod error;
 pub mod api_encoder;
+pub mod keys;
+pub mod tx;
+pub mod block;
+pub mod mpt;
+pub mod state;
 
 // TODO WASM
 // use wasm_bindgen::prelude::*;
@@ -14,3 +22,10 @@ pub mod api_encoder;
 static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
 
 pub type Bytes = Vec<u8>;
+
+/// Blake2b hash with a 32-byte digest, used for all hashes of the protocol.
+pub(crate) fn hash(data: &[u8]) -> [u8; 32] {
+    use blake2::{digest::consts::U32, Blake2b, Digest};
+    type Blake2b32 = Blake2b<U32>;
+    Blake2b32::digest(data).into()
+}
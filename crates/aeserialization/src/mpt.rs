@@ -0,0 +1,498 @@
+//! Merkle Patricia trees, which hold the state of the chain. Keys are split into nibbles, and nodes
+//! are referenced by the blake2b hash of their RLP encoding, regardless of their size. A tree may
+//! hold only a subset of its nodes, as in a proof of inclusion.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::error::DecodingErr;
+use crate::rlp::{FromRlpItem, FromRlpRef, RlpItem, RlpRef, ToRlpItem};
+use crate::{hash, Bytes};
+
+pub mod poi;
+pub use poi::*;
+
+/// Hash of a node.
+pub type NodeHash = [u8; 32];
+
+/// An error while accessing a tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MptErr {
+    /// Node is not a part of the tree, eg. when looking up a key not covered by a proof.
+    MissingNode { hash: NodeHash },
+    /// Empty values are reserved for absent keys.
+    EmptyValue,
+    /// Proof of inclusion does not cover the state tree.
+    MissingTree,
+    /// Trees being merged have different roots.
+    RootMismatch,
+}
+
+impl fmt::Display for MptErr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MptErr::MissingNode { .. } => f.write_str("node is missing from the tree"),
+            MptErr::EmptyValue => f.write_str("empty values cannot be stored"),
+            MptErr::MissingTree => f.write_str("state tree is missing from the proof"),
+            MptErr::RootMismatch => f.write_str("trees have different roots"),
+        }
+    }
+}
+
+impl std::error::Error for MptErr {}
+
+/// Node of a tree. Paths are sequences of nibbles.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Node {
+    /// Remaining path of a key along with its value.
+    Leaf { path: Vec<u8>, value: Bytes },
+    /// Path shared by all keys of the child node.
+    Extension { path: Vec<u8>, child: NodeHash },
+    /// Children indexed by the next nibble, and the value of the key ending at the node, if any.
+    Branch {
+        children: Box<[Option<NodeHash>; 16]>,
+        value: Bytes,
+    },
+}
+
+impl Node {
+    pub fn hash(&self) -> NodeHash {
+        hash(&self.serialize_rlp())
+    }
+}
+
+/// Encodes a path as bytes, with the node type and path parity in the first nibble.
+fn encode_path(path: &[u8], leaf: bool) -> Bytes {
+    let flag = if leaf { 2 } else { 0 };
+    let (first, rest) = if path.len() % 2 == 1 {
+        ((flag + 1) << 4 | path[0], &path[1..])
+    } else {
+        (flag << 4, path)
+    };
+    let mut out = vec![first];
+    out.extend(rest.chunks(2).map(|pair| pair[0] << 4 | pair[1]));
+    out
+}
+
+/// Decodes a path. Returns whether it belongs to a leaf.
+fn decode_path(bytes: &[u8]) -> Result<(Vec<u8>, bool), DecodingErr> {
+    let (&first, rest) = bytes.split_first().ok_or(DecodingErr::InvalidNode)?;
+    let mut path = match first >> 4 {
+        0 | 2 if first & 0xf == 0 => Vec::with_capacity(rest.len() * 2),
+        1 | 3 => vec![first & 0xf],
+        _ => Err(DecodingErr::InvalidNode)?,
+    };
+    path.extend(rest.iter().flat_map(|b| [b >> 4, b & 0xf]));
+    Ok((path, first >> 4 >= 2))
+}
+
+fn nibbles(key: &[u8]) -> Vec<u8> {
+    key.iter().flat_map(|b| [b >> 4, b & 0xf]).collect()
+}
+
+fn common_prefix(a: &[u8], b: &[u8]) -> usize {
+    a.iter().zip(b).take_while(|(x, y)| x == y).count()
+}
+
+impl ToRlpItem for Node {
+    fn to_rlp_item(&self) -> RlpItem {
+        match self {
+            Node::Leaf { path, value } => RlpItem::List(vec![
+                RlpItem::ByteArray(encode_path(path, true)),
+                RlpItem::ByteArray(value.clone()),
+            ]),
+            Node::Extension { path, child } => RlpItem::List(vec![
+                RlpItem::ByteArray(encode_path(path, false)),
+                RlpItem::ByteArray(child.to_vec()),
+            ]),
+            Node::Branch { children, value } => RlpItem::List(
+                children
+                    .iter()
+                    .map(|child| RlpItem::ByteArray(child.map_or(vec![], |h| h.to_vec())))
+                    .chain([RlpItem::ByteArray(value.clone())])
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl Node {
+    fn from_items(items: &[&[u8]]) -> Result<Self, DecodingErr> {
+        let hash = |bytes: &[u8]| bytes.try_into().map_err(|_| DecodingErr::InvalidNode);
+        match items {
+            [path, x] => match decode_path(path)? {
+                (path, true) => Ok(Node::Leaf {
+                    path,
+                    value: x.to_vec(),
+                }),
+                (path, false) => Ok(Node::Extension {
+                    path,
+                    child: hash(x)?,
+                }),
+            },
+            [children @ .., value] if children.len() == 16 => {
+                let mut hashes = [None; 16];
+                for (h, child) in hashes.iter_mut().zip(children) {
+                    if !child.is_empty() {
+                        *h = Some(hash(child)?);
+                    }
+                }
+                Ok(Node::Branch {
+                    children: Box::new(hashes),
+                    value: value.to_vec(),
+                })
+            }
+            _ => Err(DecodingErr::InvalidNode),
+        }
+    }
+}
+
+impl FromRlpItem for Node {
+    fn from_rlp_item(item: &RlpItem) -> Result<Self, DecodingErr> {
+        let items = item
+            .as_list()?
+            .iter()
+            .map(|x| x.as_bytes())
+            .collect::<Result<Vec<_>, _>>()?;
+        Node::from_items(&items)
+    }
+}
+
+impl<'a> FromRlpRef<'a> for Node {
+    fn from_rlp_ref(item: &RlpRef<'a>) -> Result<Self, DecodingErr> {
+        let items = item
+            .list()?
+            .map(|x| x?.byte_array())
+            .collect::<Result<Vec<_>, DecodingErr>>()?;
+        Node::from_items(&items)
+    }
+}
+
+/// Merkle Patricia tree held in memory. Nodes replaced by insertions are kept, as they may be
+/// shared by other parts of the tree.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Mpt {
+    root: Option<NodeHash>,
+    nodes: HashMap<NodeHash, Node>,
+}
+
+impl Mpt {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a possibly partial tree out of its nodes.
+    pub fn from_nodes(root: Option<NodeHash>, nodes: impl IntoIterator<Item = Node>) -> Self {
+        Mpt {
+            root,
+            nodes: nodes.into_iter().map(|n| (n.hash(), n)).collect(),
+        }
+    }
+
+    /// Hash of the root node, or [None] if the tree is empty.
+    pub fn root_hash(&self) -> Option<NodeHash> {
+        self.root
+    }
+
+    /// Nodes of the tree, including the ones replaced by insertions.
+    pub fn nodes(&self) -> impl Iterator<Item = (&NodeHash, &Node)> {
+        self.nodes.iter()
+    }
+
+    fn node(&self, hash: &NodeHash) -> Result<&Node, MptErr> {
+        self.nodes
+            .get(hash)
+            .ok_or(MptErr::MissingNode { hash: *hash })
+    }
+
+    fn store(&mut self, node: Node) -> NodeHash {
+        let hash = node.hash();
+        self.nodes.insert(hash, node);
+        hash
+    }
+
+    /// Visits the nodes on the path of a key, and returns its value.
+    fn walk<'a>(
+        &'a self,
+        key: &[u8],
+        mut visit: impl FnMut(&'a Node),
+    ) -> Result<Option<&'a [u8]>, MptErr> {
+        let path = nibbles(key);
+        let mut rest = &path[..];
+        let mut next = self.root;
+        while let Some(hash) = next {
+            let node = self.node(&hash)?;
+            visit(node);
+            match node {
+                Node::Leaf { path, value } => {
+                    return Ok((path[..] == *rest).then_some(&value[..]));
+                }
+                Node::Extension { path, child } => match rest.strip_prefix(&path[..]) {
+                    Some(tail) => {
+                        rest = tail;
+                        next = Some(*child);
+                    }
+                    None => return Ok(None),
+                },
+                Node::Branch { children, value } => match rest.split_first() {
+                    Some((i, tail)) => {
+                        rest = tail;
+                        next = children[*i as usize];
+                    }
+                    None => return Ok((!value.is_empty()).then_some(&value[..])),
+                },
+            }
+        }
+        Ok(None)
+    }
+
+    /// Looks up the value of a key. Fails if the tree lacks a node needed to tell whether the key
+    /// is present.
+    pub fn get(&self, key: &[u8]) -> Result<Option<&[u8]>, MptErr> {
+        self.walk(key, |_| {})
+    }
+
+    /// Extracts the nodes needed to look up a key, proving either its value or its absence.
+    pub fn prove(&self, key: &[u8]) -> Result<Mpt, MptErr> {
+        let mut nodes = Vec::new();
+        self.walk(key, |node| nodes.push(node.clone()))?;
+        Ok(Mpt::from_nodes(self.root, nodes))
+    }
+
+    /// Sets the value of a key.
+    pub fn insert(&mut self, key: &[u8], value: Bytes) -> Result<(), MptErr> {
+        if value.is_empty() {
+            Err(MptErr::EmptyValue)?;
+        }
+        self.root = Some(self.insert_at(self.root, &nibbles(key), value)?);
+        Ok(())
+    }
+
+    /// Inserts a value below the given node. Returns the hash of the node replacing it.
+    fn insert_at(
+        &mut self,
+        at: Option<NodeHash>,
+        path: &[u8],
+        value: Bytes,
+    ) -> Result<NodeHash, MptErr> {
+        let Some(hash) = at else {
+            return Ok(self.store(Node::Leaf {
+                path: path.to_vec(),
+                value,
+            }));
+        };
+
+        let node = match self.node(&hash)?.clone() {
+            Node::Leaf { path: p, .. } if p == path => Node::Leaf { path: p, value },
+            Node::Leaf { path: p, value: v } => {
+                let n = common_prefix(&p, path);
+                let mut branch = Node::Branch {
+                    children: Box::new([None; 16]),
+                    value: vec![],
+                };
+                self.put_in_branch(&mut branch, &p[n..], v);
+                self.put_in_branch(&mut branch, &path[n..], value);
+                self.with_extension(&path[..n], branch)
+            }
+            Node::Extension { path: p, child } if path.starts_with(&p) => {
+                let child = self.insert_at(Some(child), &path[p.len()..], value)?;
+                Node::Extension { path: p, child }
+            }
+            Node::Extension { path: p, child } => {
+                let n = common_prefix(&p, path);
+                let mut children = [None; 16];
+                children[p[n] as usize] = Some(match &p[n + 1..] {
+                    [] => child,
+                    tail => self.store(Node::Extension {
+                        path: tail.to_vec(),
+                        child,
+                    }),
+                });
+                let mut branch = Node::Branch {
+                    children: Box::new(children),
+                    value: vec![],
+                };
+                self.put_in_branch(&mut branch, &path[n..], value);
+                self.with_extension(&path[..n], branch)
+            }
+            Node::Branch {
+                mut children,
+                value: v,
+            } => match path.split_first() {
+                Some((i, tail)) => {
+                    let i = *i as usize;
+                    children[i] = Some(self.insert_at(children[i], tail, value)?);
+                    Node::Branch { children, value: v }
+                }
+                None => Node::Branch { children, value },
+            },
+        };
+        Ok(self.store(node))
+    }
+
+    /// Puts a value into a new branch node, either as the value of the node or as a leaf child.
+    fn put_in_branch(&mut self, branch: &mut Node, path: &[u8], v: Bytes) {
+        let Node::Branch { children, value } = branch else {
+            unreachable!("not a branch node");
+        };
+        match path.split_first() {
+            Some((i, tail)) => {
+                children[*i as usize] = Some(self.store(Node::Leaf {
+                    path: tail.to_vec(),
+                    value: v,
+                }));
+            }
+            None => *value = v,
+        }
+    }
+
+    /// Prefixes a new branch node with an extension, unless the path is empty.
+    fn with_extension(&mut self, path: &[u8], branch: Node) -> Node {
+        if path.is_empty() {
+            branch
+        } else {
+            Node::Extension {
+                path: path.to_vec(),
+                child: self.store(branch),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use std::collections::BTreeMap;
+
+    fn build(entries: &BTreeMap<Vec<u8>, Vec<u8>>) -> Mpt {
+        let mut tree = Mpt::new();
+        for (k, v) in entries {
+            tree.insert(k, v.clone()).expect("insertion failed");
+        }
+        tree
+    }
+
+    fn entries() -> impl Strategy<Value = BTreeMap<Vec<u8>, Vec<u8>>> {
+        let key = prop::collection::vec(0..4u8, 0..4);
+        let value = prop::collection::vec(any::<u8>(), 1..8);
+        prop::collection::btree_map(key, value, 0..16)
+    }
+
+    proptest! {
+        #[test]
+        fn insert_get(entries in entries(), absent in prop::collection::vec(0..4u8, 0..4)) {
+            let tree = build(&entries);
+            for (k, v) in &entries {
+                prop_assert_eq!(tree.get(k), Ok(Some(&v[..])));
+            }
+            if !entries.contains_key(&absent) {
+                prop_assert_eq!(tree.get(&absent), Ok(None));
+            }
+        }
+
+        #[test]
+        fn order_independent_root(entries in entries()) {
+            let mut reversed = Mpt::new();
+            for (k, v) in entries.iter().rev() {
+                reversed.insert(k, v.clone()).expect("insertion failed");
+            }
+            prop_assert_eq!(reversed.root_hash(), build(&entries).root_hash());
+        }
+
+        #[test]
+        fn proofs(entries in entries(), key in prop::collection::vec(0..4u8, 0..4)) {
+            let tree = build(&entries);
+            let proof = tree.prove(&key).expect("proving failed");
+            prop_assert_eq!(proof.root_hash(), tree.root_hash());
+            prop_assert_eq!(proof.get(&key), tree.get(&key));
+        }
+
+        #[test]
+        fn node_roundtrip(path in prop::collection::vec(0..16u8, 0..8), value: [u8; 3]) {
+            let nodes = [
+                Node::Leaf { path: path.clone(), value: value.to_vec() },
+                Node::Extension { path, child: [7; 32] },
+            ];
+            for node in nodes {
+                prop_assert_eq!(Node::deserialize_rlp(&node.serialize_rlp()), Ok(node.clone()));
+                prop_assert_eq!(Node::deserialize_rlp_ref(&node.serialize_rlp()), Ok(node));
+            }
+        }
+    }
+
+    #[test]
+    fn path_encoding() {
+        assert_eq!(encode_path(&[1, 2, 3], true), vec![0x31, 0x23]);
+        assert_eq!(encode_path(&[1, 2], true), vec![0x20, 0x12]);
+        assert_eq!(encode_path(&[1], false), vec![0x11]);
+        assert_eq!(encode_path(&[], false), vec![0x00]);
+        assert_eq!(decode_path(&[0x31, 0x23]), Ok((vec![1, 2, 3], true)));
+        assert_eq!(decode_path(&[0x00]), Ok((vec![], false)));
+        assert_eq!(decode_path(&[0x21]), Err(DecodingErr::InvalidNode));
+        assert_eq!(decode_path(&[]), Err(DecodingErr::InvalidNode));
+    }
+
+    #[test]
+    fn single_leaf() {
+        let mut tree = Mpt::new();
+        assert_eq!(tree.root_hash(), None);
+        tree.insert(&[0xab], vec![1]).unwrap();
+
+        let leaf = RlpItem::List(vec![
+            RlpItem::ByteArray(vec![0x20, 0xab]),
+            RlpItem::ByteArray(vec![1]),
+        ]);
+        assert_eq!(tree.root_hash(), Some(hash(&leaf.serialize())));
+    }
+
+    #[test]
+    fn branch_layout() {
+        let mut tree = Mpt::new();
+        tree.insert(&[0x12, 0x34], vec![1]).unwrap();
+        tree.insert(&[0x12, 0x35], vec![2]).unwrap();
+        tree.insert(&[], vec![3]).unwrap();
+
+        let leaf = |v| Node::Leaf {
+            path: vec![],
+            value: vec![v],
+        };
+        let mut children = [None; 16];
+        children[4] = Some(leaf(1).hash());
+        children[5] = Some(leaf(2).hash());
+        let inner = Node::Branch {
+            children: Box::new(children),
+            value: vec![],
+        };
+        // The extension is split at its first nibble, which moves to the new branch.
+        let ext = Node::Extension {
+            path: vec![2, 3],
+            child: inner.hash(),
+        };
+        let mut children = [None; 16];
+        children[1] = Some(ext.hash());
+        let root = Node::Branch {
+            children: Box::new(children),
+            value: vec![3],
+        };
+        assert_eq!(tree.root_hash(), Some(root.hash()));
+        assert_eq!(root.to_rlp_item().as_list().unwrap().len(), 17);
+        assert_eq!(Node::deserialize_rlp(&root.serialize_rlp()), Ok(root));
+    }
+
+    #[test]
+    fn missing_nodes() {
+        let mut tree = Mpt::new();
+        tree.insert(&[0x10], vec![1]).unwrap();
+        tree.insert(&[0x20], vec![2]).unwrap();
+        let proof = tree.prove(&[0x10]).unwrap();
+        assert_eq!(proof.get(&[0x10]), Ok(Some(&[1][..])));
+        assert!(matches!(
+            proof.get(&[0x20]),
+            Err(MptErr::MissingNode { .. })
+        ));
+        // Absence is proven by the root alone, as no child is at the first nibble.
+        assert_eq!(proof.get(&[0x30]), Ok(None));
+        assert_eq!(tree.clone().insert(&[1], vec![]), Err(MptErr::EmptyValue));
+    }
+}
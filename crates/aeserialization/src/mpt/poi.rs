@@ -0,0 +1,314 @@
+//! Proofs of inclusion (`pi_`). A proof holds the nodes of state trees needed to look up some of
+//! their keys, and is checked against the state hash: the root hash of a tree mapping the name of
+//! each state tree to its root hash. Block headers (`bs_`) and state channels (`st_`) commit to the
+//! state through that hash.
+
+use super::*;
+use crate::api_encoder;
+
+/// Identifier tag of a proof of inclusion.
+pub const POI_TAG: u16 = 60;
+/// Proof of inclusion format version.
+pub const POI_VSN: u8 = 1;
+
+/// State trees, in the order of serialization.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum StateTree {
+    Accounts,
+    Calls,
+    Channels,
+    Contracts,
+    Ns,
+    Oracles,
+}
+
+impl StateTree {
+    /// All state trees, sorted by name.
+    pub const ALL: [StateTree; 6] = [
+        StateTree::Accounts,
+        StateTree::Calls,
+        StateTree::Channels,
+        StateTree::Contracts,
+        StateTree::Ns,
+        StateTree::Oracles,
+    ];
+
+    /// Key of the tree root in the tree of state tree roots.
+    pub fn name(self) -> &'static str {
+        match self {
+            StateTree::Accounts => "accounts",
+            StateTree::Calls => "calls",
+            StateTree::Channels => "channels",
+            StateTree::Contracts => "contracts",
+            StateTree::Ns => "ns",
+            StateTree::Oracles => "oracles",
+        }
+    }
+}
+
+/// Proof of inclusion of keys in the state trees. Trees not covered by the proof are treated as
+/// empty.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Poi {
+    trees: [Option<Mpt>; 6],
+}
+
+impl Poi {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Nodes of a state tree included in the proof.
+    pub fn tree(&self, tree: StateTree) -> Option<&Mpt> {
+        self.trees[tree as usize].as_ref()
+    }
+
+    /// Adds the nodes proving the value or the absence of a key of a state tree. Keys of the same
+    /// tree have to be proven from a tree with the same root.
+    pub fn add(&mut self, tree: StateTree, from: &Mpt, key: &[u8]) -> Result<(), MptErr> {
+        let proof = from.prove(key)?;
+        match &mut self.trees[tree as usize] {
+            Some(known) if known.root != proof.root => Err(MptErr::RootMismatch),
+            Some(known) => {
+                known.nodes.extend(proof.nodes);
+                Ok(())
+            }
+            slot @ None => {
+                *slot = Some(proof);
+                Ok(())
+            }
+        }
+    }
+
+    /// Looks up the value of a key in a state tree.
+    pub fn lookup(&self, tree: StateTree, key: &[u8]) -> Result<Option<&[u8]>, MptErr> {
+        self.tree(tree).ok_or(MptErr::MissingTree)?.get(key)
+    }
+
+    /// State hash of the trees, to compare with a trusted one. Empty trees have a root hash of
+    /// zeros.
+    pub fn root_hash(&self) -> NodeHash {
+        let mut roots = Mpt::new();
+        for tree in StateTree::ALL {
+            let root = self.tree(tree).and_then(Mpt::root_hash).unwrap_or_default();
+            roots
+                .insert(tree.name().as_bytes(), root.to_vec())
+                .expect("root hashes are not empty");
+        }
+        roots.root_hash().expect("tree of roots is not empty")
+    }
+
+    /// Checks the proof against a trusted state hash, and looks up the value of a key. The hash
+    /// can come from a block header (`bs_`) or a state channel (`st_`).
+    pub fn verified_lookup(
+        &self,
+        state_hash: &NodeHash,
+        tree: StateTree,
+        key: &[u8],
+    ) -> Result<Option<&[u8]>, MptErr> {
+        if self.root_hash() != *state_hash {
+            Err(MptErr::RootMismatch)?;
+        }
+        self.lookup(tree, key)
+    }
+
+    /// Decodes a proof from its `pi_` encoding.
+    pub fn from_encoded(poi: &api_encoder::Poi) -> Result<Self, DecodingErr> {
+        Self::deserialize_rlp_ref(poi.as_bytes())
+    }
+
+    /// Encodes the proof as `pi_`.
+    pub fn to_encoded(&self) -> api_encoder::Poi {
+        api_encoder::Poi::new(self.serialize_rlp()).expect("proofs are not size constrained")
+    }
+}
+
+// Each tree is a list of zero or one proofs, and a proof is the root hash along with the list of
+// nodes keyed by their hashes.
+fn tree_to_rlp_item(tree: &Option<Mpt>) -> RlpItem {
+    let proof = tree.iter().map(|tree| {
+        let mut nodes: Vec<_> = tree.nodes().collect();
+        nodes.sort_by_key(|(hash, _)| *hash);
+        let nodes = nodes
+            .into_iter()
+            .map(|(hash, node)| RlpItem::List(vec![hash.to_rlp_item(), node.to_rlp_item()]))
+            .collect();
+        let root = tree.root_hash().unwrap_or_default();
+        RlpItem::List(vec![root.to_rlp_item(), RlpItem::List(nodes)])
+    });
+    RlpItem::List(proof.collect())
+}
+
+fn tree_from_rlp_ref(item: &RlpRef) -> Result<Option<Mpt>, DecodingErr> {
+    let mut proofs = item.list()?;
+    let Some(proof) = proofs.next().transpose()? else {
+        return Ok(None);
+    };
+    if proofs.next().is_some() {
+        Err(DecodingErr::InvalidList)?;
+    }
+
+    let (root, nodes): (NodeHash, Vec<(NodeHash, Node)>) = FromRlpRef::from_rlp_ref(&proof)?;
+    let mut tree = Mpt::new();
+    // Empty trees have a root hash of zeros.
+    tree.root = (root != NodeHash::default()).then_some(root);
+    for (hash, node) in nodes {
+        if node.hash() != hash {
+            Err(DecodingErr::InvalidHash)?;
+        }
+        tree.nodes.insert(hash, node);
+    }
+    Ok(Some(tree))
+}
+
+impl ToRlpItem for Poi {
+    fn to_rlp_item(&self) -> RlpItem {
+        let header = [POI_TAG.to_rlp_item(), POI_VSN.to_rlp_item()];
+        RlpItem::List(
+            header
+                .into_iter()
+                .chain(self.trees.iter().map(tree_to_rlp_item))
+                .collect(),
+        )
+    }
+}
+
+impl<'a> FromRlpRef<'a> for Poi {
+    fn from_rlp_ref(item: &RlpRef<'a>) -> Result<Self, DecodingErr> {
+        let items = item.list()?.collect::<Result<Vec<_>, _>>()?;
+        let (tag, vsn, trees) = match &items[..] {
+            [tag, vsn, trees @ ..] if trees.len() == StateTree::ALL.len() => (tag, vsn, trees),
+            _ => Err(DecodingErr::InvalidObject {
+                object: "Poi",
+                fields: 8,
+            })?,
+        };
+        if u16::from_rlp_ref(tag) != Ok(POI_TAG) {
+            Err(DecodingErr::InvalidTag { object: "Poi" })?;
+        }
+        if u8::from_rlp_ref(vsn) != Ok(POI_VSN) {
+            Err(DecodingErr::InvalidVersion { object: "Poi" })?;
+        }
+
+        let mut poi = Poi::new();
+        for (slot, item) in poi.trees.iter_mut().zip(trees) {
+            *slot = tree_from_rlp_ref(item)?;
+        }
+        Ok(poi)
+    }
+}
+
+impl FromRlpItem for Poi {
+    fn from_rlp_item(item: &RlpItem) -> Result<Self, DecodingErr> {
+        Poi::deserialize_rlp_ref(&item.serialize())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn accounts() -> Mpt {
+        let mut tree = Mpt::new();
+        for i in 0..20u8 {
+            tree.insert(&[i; 32], vec![i + 1; 10]).unwrap();
+        }
+        tree
+    }
+
+    proptest! {
+        #[test]
+        fn poi_roundtrip(keys in prop::collection::vec(0..30u8, 1..5)) {
+            let tree = accounts();
+            let mut poi = Poi::new();
+            for k in &keys {
+                poi.add(StateTree::Accounts, &tree, &[*k; 32]).expect("proving failed");
+            }
+
+            let decoded = Poi::from_encoded(&poi.to_encoded()).expect("decoding failed");
+            prop_assert_eq!(&decoded, &poi);
+            for k in &keys {
+                prop_assert_eq!(decoded.lookup(StateTree::Accounts, &[*k; 32]), tree.get(&[*k; 32]));
+            }
+        }
+    }
+
+    #[test]
+    fn state_hash() {
+        let tree = accounts();
+        let mut poi = Poi::new();
+        poi.add(StateTree::Accounts, &tree, &[3; 32]).unwrap();
+
+        let mut roots = Mpt::new();
+        for name in ["accounts", "calls", "channels", "contracts", "ns", "oracles"] {
+            let root = match name {
+                "accounts" => tree.root_hash().unwrap(),
+                _ => [0; 32],
+            };
+            roots.insert(name.as_bytes(), root.to_vec()).unwrap();
+        }
+        let state_hash = roots.root_hash().unwrap();
+        assert_eq!(poi.root_hash(), state_hash);
+
+        let value = poi.verified_lookup(&state_hash, StateTree::Accounts, &[3; 32]);
+        assert_eq!(value, Ok(Some(&[4; 10][..])));
+        let value = poi.verified_lookup(&[0; 32], StateTree::Accounts, &[3; 32]);
+        assert_eq!(value, Err(MptErr::RootMismatch));
+        assert_eq!(
+            poi.lookup(StateTree::Contracts, &[3; 32]),
+            Err(MptErr::MissingTree)
+        );
+
+        let encoded = api_encoder::State::new(state_hash.to_vec()).unwrap();
+        let state_hash = encoded.as_bytes().try_into().unwrap();
+        assert!(poi.verified_lookup(state_hash, StateTree::Accounts, &[3; 32]).is_ok());
+    }
+
+    #[test]
+    fn layout() {
+        let mut tree = Mpt::new();
+        tree.insert(&[1], vec![2]).unwrap();
+        let mut poi = Poi::new();
+        poi.add(StateTree::Calls, &tree, &[1]).unwrap();
+
+        let root = tree.root_hash().unwrap();
+        let node = tree.node(&root).unwrap().to_rlp_item();
+        let expect = RlpItem::List(vec![
+            RlpItem::ByteArray(vec![60]),
+            RlpItem::ByteArray(vec![1]),
+            RlpItem::List(vec![]),
+            RlpItem::List(vec![RlpItem::List(vec![
+                RlpItem::ByteArray(root.to_vec()),
+                RlpItem::List(vec![RlpItem::List(vec![
+                    RlpItem::ByteArray(root.to_vec()),
+                    node,
+                ])]),
+            ])]),
+            RlpItem::List(vec![]),
+            RlpItem::List(vec![]),
+            RlpItem::List(vec![]),
+            RlpItem::List(vec![]),
+        ]);
+        assert_eq!(poi.to_rlp_item(), expect);
+    }
+
+    #[test]
+    fn tampered_nodes() {
+        let tree = accounts();
+        let mut poi = Poi::new();
+        poi.add(StateTree::Accounts, &tree, &[3; 32]).unwrap();
+
+        let mut bytes = poi.serialize_rlp();
+        let value = bytes.windows(10).position(|w| w == [4; 10]).unwrap();
+        bytes[value] ^= 1;
+        assert_eq!(Poi::deserialize_rlp(&bytes), Err(DecodingErr::InvalidHash));
+
+        let mut other = Mpt::new();
+        other.insert(&[3; 32], vec![1]).unwrap();
+        assert_eq!(
+            poi.add(StateTree::Accounts, &other, &[3; 32]),
+            Err(MptErr::RootMismatch)
+        );
+    }
+}
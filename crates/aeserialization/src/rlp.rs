@@ -1,6 +1,18 @@
 use crate::{error, Bytes};
 use num_traits::ToPrimitive;
 
+pub use aeser_derive::{FromRlpItem, FromRlpRef, ToRlpItem};
+
+pub mod de;
+pub mod pretty;
+pub mod ser;
+pub mod stream;
+
+pub use de::{from_bytes, Deserializer};
+pub use pretty::ParseErr;
+pub use ser::{to_bytes, to_item, SerdeErr, Serializer};
+pub use stream::{RlpReader, RlpWriter, StreamErr};
+
 /// Max single-byte size description
 const UNTAGGED_SIZE_LIMIT: u8 = 55;
 /// Max single-byte value
@@ -34,46 +46,134 @@ pub enum RlpItem {
 }
 
 impl RlpItem {
-    /// Unpack as a byte array.
+    /// Unpack as a byte array. Copies the contents, see [RlpItem::as_bytes] for a borrowing
+    /// alternative.
     pub fn byte_array(&self) -> Result<Bytes, error::DecodingErr> {
+        self.as_bytes().map(|x| x.to_vec())
+    }
+
+    /// Unpack as a list of Rlp items. Copies the elements, see [RlpItem::as_list] for a borrowing
+    /// alternative.
+    pub fn list(&self) -> Result<Vec<RlpItem>, error::DecodingErr> {
+        self.as_list().map(|x| x.to_vec())
+    }
+
+    /// Borrow the contents of a byte array.
+    pub fn as_bytes(&self) -> Result<&[u8], error::DecodingErr> {
         match self {
-            RlpItem::ByteArray(arr) => Ok(arr.to_vec()),
+            RlpItem::ByteArray(arr) => Ok(arr),
             RlpItem::List(_) => Err(error::DecodingErr::InvalidBinary),
         }
     }
 
-    /// Unpack as a list of Rlp items.
-    pub fn list(&self) -> Result<Vec<RlpItem>, error::DecodingErr> {
+    /// Borrow the elements of a list.
+    pub fn as_list(&self) -> Result<&[RlpItem], error::DecodingErr> {
         match self {
             RlpItem::ByteArray(_) => Err(error::DecodingErr::InvalidList),
-            RlpItem::List(l) => Ok(l.to_vec()),
+            RlpItem::List(l) => Ok(l),
         }
     }
 
+    /// Looks up a nested item. Each index of `path` selects an element of a list, starting from
+    /// this item. On failure, the error holds the path up to the step which could not be taken.
+    pub fn at(&self, path: &[usize]) -> Result<&RlpItem, error::DecodingErr> {
+        path.iter()
+            .enumerate()
+            .try_fold(self, |item, (depth, &index)| {
+                let fail = |err| error::DecodingErr::InvalidPath {
+                    path: path[..=depth].to_vec(),
+                    err: Box::new(err),
+                };
+                let items = item.as_list().map_err(fail)?;
+                items.get(index).ok_or_else(|| {
+                    fail(error::DecodingErr::IndexOutOfBounds {
+                        index,
+                        len: items.len(),
+                    })
+                })
+            })
+    }
+
+    /// Decodes the element at `index` of a list.
+    pub fn get<T: FromRlpItem>(&self, index: usize) -> Result<T, error::DecodingErr> {
+        self.get_at(&[index])
+    }
+
+    /// Decodes a nested item, located as in [RlpItem::at].
+    pub fn get_at<T: FromRlpItem>(&self, path: &[usize]) -> Result<T, error::DecodingErr> {
+        T::from_rlp_item(self.at(path)?).map_err(|err| error::DecodingErr::InvalidPath {
+            path: path.to_vec(),
+            err: Box::new(err),
+        })
+    }
+
     /// Serializes an [RlpItem] into bytes.
     pub fn serialize(&self) -> Bytes {
+        let mut out = Vec::new();
+        self.serialize_into(&mut out);
+        out
+    }
+
+    /// Appends the serialized item to `out`. The buffer grows at most once, and every byte is
+    /// written exactly once, regardless of the nesting depth.
+    pub fn serialize_into(&self, out: &mut Vec<u8>) {
+        let mut list_lens = Vec::new();
+        let len = self.collect_list_lens(&mut list_lens);
+        out.reserve(len);
+        self.write_into(out, &list_lens, &mut 0);
+    }
+
+    /// Computes the encoded length, recording payload sizes of all nested lists in pre-order.
+    pub(crate) fn collect_list_lens(&self, lens: &mut Vec<usize>) -> usize {
+        match self {
+            RlpItem::ByteArray(_) => self.encoded_len(),
+            RlpItem::List(items) => {
+                let slot = lens.len();
+                lens.push(0);
+                let len = items.iter().map(|x| x.collect_list_lens(lens)).sum();
+                lens[slot] = len;
+                list_encoded_len(len)
+            }
+        }
+    }
+
+    /// Writes the item using list payload sizes from [RlpItem::collect_list_lens], starting at
+    /// index `next`.
+    fn write_into(&self, out: &mut Vec<u8>, list_lens: &[usize], next: &mut usize) {
         match self {
+            RlpItem::ByteArray(bytes) if bytes.len() == 1 && bytes[0] <= UNTAGGED_LIMIT => {
+                out.push(bytes[0])
+            }
             RlpItem::ByteArray(bytes) => {
-                if bytes.len() == 1 && bytes[0] <= UNTAGGED_LIMIT {
-                    bytes.to_vec()
-                } else {
-                    Self::add_size(BYTE_ARRAY_OFFSET, bytes.to_vec())
-                }
+                let (header, header_len) = encode_header(BYTE_ARRAY_OFFSET, bytes.len());
+                out.extend_from_slice(&header[..header_len]);
+                out.extend_from_slice(bytes);
             }
             RlpItem::List(items) => {
-                let bytes: Bytes = items.iter().flat_map(|x| x.serialize()).collect();
-                Self::add_size(LIST_OFFSET, bytes)
+                let (header, header_len) = encode_header(LIST_OFFSET, list_lens[*next]);
+                *next += 1;
+                out.extend_from_slice(&header[..header_len]);
+                items.iter().for_each(|x| x.write_into(out, list_lens, next));
             }
         }
     }
 
-    /// Deserializes an [RlpItem]. Requires consuming the entire input.
+    /// Deserializes an [RlpItem] with default [DecodingLimits]. Requires consuming the entire
+    /// input.
     pub fn deserialize(bytes: &[u8]) -> Result<RlpItem, DecodingErr> {
+        Self::deserialize_with_limits(bytes, &DecodingLimits::default())
+    }
+
+    /// Deserializes an [RlpItem] within given limits. Requires consuming the entire input.
+    pub fn deserialize_with_limits(
+        bytes: &[u8],
+        limits: &DecodingLimits,
+    ) -> Result<RlpItem, DecodingErr> {
         if bytes.is_empty() {
             Err(DecodingErr::Empty)?;
         }
 
-        match Self::try_deserialize(bytes)? {
+        match Self::try_deserialize_with_limits(bytes, limits)? {
             (item, []) => Ok(item),
             (item, rest) => Err(DecodingErr::Trailing {
                 input: bytes.to_vec(),
@@ -83,116 +183,370 @@ impl RlpItem {
         }
     }
 
-    /// Deserializes an [RlpItem]. Returns trailing input which was not consumed.
+    /// Deserializes an [RlpItem] with default [DecodingLimits]. Returns trailing input which was
+    /// not consumed.
     pub fn try_deserialize(bytes: &[u8]) -> Result<(RlpItem, &[u8]), DecodingErr> {
-        Self::try_decode_at(bytes, 0)
+        Self::try_deserialize_with_limits(bytes, &DecodingLimits::default())
     }
 
-    fn try_decode_at(bytes: &[u8], at: usize) -> Result<(RlpItem, &[u8]), DecodingErr> {
-        let res = match bytes[0] {
-            ..=UNTAGGED_LIMIT => (RlpItem::ByteArray(bytes[0..1].to_vec()), &bytes[1..]),
-            BYTE_ARRAY_OFFSET..=BYTE_ARRAY_UNTAGGED_LIMIT => {
-                let len: usize = (bytes[0] - BYTE_ARRAY_OFFSET) as usize;
+    /// Deserializes an [RlpItem] within given limits. Returns trailing input which was not
+    /// consumed.
+    pub fn try_deserialize_with_limits<'a>(
+        bytes: &'a [u8],
+        limits: &DecodingLimits,
+    ) -> Result<(RlpItem, &'a [u8]), DecodingErr> {
+        let (item, rest) = RlpRef::try_deserialize_with_limits(bytes, limits)?;
+        Ok((item.to_item()?, rest))
+    }
 
-                if bytes.len() < len + 1 {
-                    Err(DecodingErr::SizeOverflow {
-                        position: at,
-                        expected: len,
-                        actual: bytes.len(),
-                    })?
-                }
+    /// Number of bytes taken by the serialized item. Computed without serializing it.
+    pub fn encoded_len(&self) -> usize {
+        match self {
+            RlpItem::ByteArray(bytes) => bytes_encoded_len(bytes),
+            RlpItem::List(items) => list_encoded_len(items.iter().map(|x| x.encoded_len()).sum()),
+        }
+    }
+}
+
+/// Encodes the size description of an item with a payload of `len` bytes. Returns a buffer along
+/// with the number of its bytes in use.
+fn encode_header(offset: u8, len: usize) -> ([u8; 9], usize) {
+    let mut header = [0; 9];
+    if len <= UNTAGGED_SIZE_LIMIT as usize {
+        header[0] = offset + len as u8;
+        (header, 1)
+    } else {
+        let size_bytes = usize_to_min_be_bytes(len);
+        let tagged_size = (UNTAGGED_SIZE_LIMIT as usize + offset as usize + size_bytes.len())
+            .to_u8()
+            .expect("Large tagged size");
+
+        header[0] = tagged_size;
+        header[1..size_bytes.len() + 1].copy_from_slice(&size_bytes);
+        (header, size_bytes.len() + 1)
+    }
+}
+
+/// Number of bytes taken by a serialized byte array.
+fn bytes_encoded_len(bytes: &[u8]) -> usize {
+    if bytes.len() == 1 && bytes[0] <= UNTAGGED_LIMIT {
+        1
+    } else {
+        header_len(bytes.len()) + bytes.len()
+    }
+}
+
+/// Number of bytes taken by a serialized list with a payload of `len` bytes.
+fn list_encoded_len(len: usize) -> usize {
+    header_len(len) + len
+}
+
+/// Number of bytes taken by the size description of an item with a payload of `len` bytes.
+fn header_len(len: usize) -> usize {
+    if len <= UNTAGGED_SIZE_LIMIT as usize {
+        1
+    } else {
+        len.ilog(256) as usize + 2
+    }
+}
+
+/// A borrowed view of an RLP-encoded value. Byte arrays point straight into the input, while lists
+/// keep their encoded payload and decode elements lazily on iteration. Use [RlpRef::to_item] to
+/// obtain an owned [RlpItem].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RlpRef<'a> {
+    ByteArray(&'a [u8]),
+    List(RlpListRef<'a>),
+}
+
+/// Encoded payload of an RLP list, along with its position in the original input.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RlpListRef<'a> {
+    payload: &'a [u8],
+    at: usize,
+    /// How many more levels of lists may be nested in the elements.
+    depth_left: usize,
+}
+
+/// Iterator over the elements of an [RlpListRef]. Stops after the first decoding error.
+#[derive(Debug, Clone)]
+pub struct RlpListIter<'a> {
+    rest: &'a [u8],
+    at: usize,
+    depth_left: usize,
+}
+
+/// Constraints imposed on decoded input. Exceeding any of them results in an error rather than
+/// unbounded recursion or memory use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecodingLimits {
+    /// Maximum nesting depth. A byte array has depth 0, while a list is one level deeper than its
+    /// deepest element.
+    pub max_depth: usize,
+    /// Maximum size of an encoded top-level item in bytes.
+    pub max_size: usize,
+}
+
+impl DecodingLimits {
+    /// Nesting depth allowed by default. Way above what any chain object needs, yet low enough to
+    /// protect the stack when decoding recursively.
+    pub const DEFAULT_MAX_DEPTH: usize = 128;
+}
+
+impl Default for DecodingLimits {
+    fn default() -> Self {
+        DecodingLimits {
+            max_depth: Self::DEFAULT_MAX_DEPTH,
+            max_size: usize::MAX,
+        }
+    }
+}
+
+impl<'a> RlpRef<'a> {
+    /// Unpack as a byte array borrowed from the input.
+    pub fn byte_array(&self) -> Result<&'a [u8], error::DecodingErr> {
+        match self {
+            RlpRef::ByteArray(arr) => Ok(arr),
+            RlpRef::List(_) => Err(error::DecodingErr::InvalidBinary),
+        }
+    }
 
-                (
-                    RlpItem::ByteArray(bytes[1..len + 1].to_vec()),
-                    &bytes[len + 1..],
-                )
+    /// Unpack as an iterator over list elements.
+    pub fn list(&self) -> Result<RlpListIter<'a>, error::DecodingErr> {
+        match self {
+            RlpRef::ByteArray(_) => Err(error::DecodingErr::InvalidList),
+            RlpRef::List(l) => Ok(l.iter()),
+        }
+    }
+
+    /// Copies the referenced data into an owned [RlpItem]. Fails if any nested list is malformed.
+    pub fn to_item(&self) -> Result<RlpItem, DecodingErr> {
+        match self {
+            RlpRef::ByteArray(arr) => Ok(RlpItem::ByteArray(arr.to_vec())),
+            RlpRef::List(l) => l
+                .iter()
+                .map(|x| x.and_then(|x| x.to_item()))
+                .collect::<Result<_, _>>()
+                .map(RlpItem::List),
+        }
+    }
+
+    /// Decodes an [RlpRef] with default [DecodingLimits]. Requires consuming the entire input.
+    /// Nested lists are not validated until they are iterated.
+    pub fn deserialize(bytes: &'a [u8]) -> Result<RlpRef<'a>, DecodingErr> {
+        Self::deserialize_with_limits(bytes, &DecodingLimits::default())
+    }
+
+    /// Decodes an [RlpRef] within given limits. Requires consuming the entire input. Nested lists
+    /// are not validated until they are iterated.
+    pub fn deserialize_with_limits(
+        bytes: &'a [u8],
+        limits: &DecodingLimits,
+    ) -> Result<RlpRef<'a>, DecodingErr> {
+        match Self::try_deserialize_with_limits(bytes, limits)? {
+            (item, []) => Ok(item),
+            (item, rest) => Err(DecodingErr::Trailing {
+                input: bytes.to_vec(),
+                undecoded: rest.to_vec(),
+                decoded: item.to_item()?,
+            }),
+        }
+    }
+
+    /// Decodes an [RlpRef] with default [DecodingLimits]. Returns trailing input which was not
+    /// consumed.
+    pub fn try_deserialize(bytes: &'a [u8]) -> Result<(RlpRef<'a>, &'a [u8]), DecodingErr> {
+        Self::try_deserialize_with_limits(bytes, &DecodingLimits::default())
+    }
+
+    /// Decodes an [RlpRef] within given limits. Returns trailing input which was not consumed.
+    pub fn try_deserialize_with_limits(
+        bytes: &'a [u8],
+        limits: &DecodingLimits,
+    ) -> Result<(RlpRef<'a>, &'a [u8]), DecodingErr> {
+        let (item, rest) = Self::try_decode_at(bytes, 0, limits.max_depth)?;
+
+        let size = bytes.len() - rest.len();
+        if size > limits.max_size {
+            Err(DecodingErr::SizeLimitExceeded {
+                position: 0,
+                limit: limits.max_size,
+                actual: size,
+            })?
+        }
+
+        Ok((item, rest))
+    }
+
+    /// Decodes an item located at position `at` of the original input, allowing `depth_left`
+    /// levels of nested lists.
+    pub(crate) fn try_decode_at(
+        bytes: &'a [u8],
+        at: usize,
+        depth_left: usize,
+    ) -> Result<(RlpRef<'a>, &'a [u8]), DecodingErr> {
+        let header = Header::decode(bytes, at)?;
+
+        if bytes.len() - header.header_len < header.len {
+            Err(DecodingErr::SizeOverflow {
+                position: at,
+                expected: header.total_len(),
+                actual: bytes.len(),
+            })?
+        }
+
+        let payload = &bytes[header.header_len..header.header_len + header.len];
+        let rest = &bytes[header.header_len + header.len..];
+
+        let item = if !header.is_list {
+            if header.header_len == 1 && header.len == 1 && payload[0] <= UNTAGGED_LIMIT {
+                Err(DecodingErr::NonCanonicalSingleByte { position: at })?
             }
+            RlpRef::ByteArray(payload)
+        } else if depth_left == 0 {
+            Err(DecodingErr::DepthLimitExceeded { position: at })?
+        } else {
+            RlpRef::List(RlpListRef {
+                payload,
+                at: at + header.header_len,
+                depth_left: depth_left - 1,
+            })
+        };
+
+        Ok((item, rest))
+    }
+}
+
+/// Size description of an encoded item.
+pub(crate) struct Header {
+    pub is_list: bool,
+    /// Number of bytes taken by the size description itself. Single bytes below 128 have none.
+    pub header_len: usize,
+    /// Number of bytes of the payload.
+    pub len: usize,
+}
+
+impl Header {
+    /// Number of bytes of a size description starting with the given byte.
+    pub fn size_of(first: u8) -> usize {
+        match first {
+            ..=UNTAGGED_LIMIT => 0,
+            BYTE_ARRAY_OFFSET..=BYTE_ARRAY_UNTAGGED_LIMIT => 1,
             BYTE_ARRAY_TAGGED_OFFSET..=BYTE_ARRAY_LIMIT => {
-                let len_bytes: usize = (bytes[0] - BYTE_ARRAY_UNTAGGED_LIMIT) as usize;
+                (first - BYTE_ARRAY_UNTAGGED_LIMIT) as usize + 1
+            }
+            LIST_OFFSET..=LIST_UNTAGGED_LIMIT => 1,
+            LIST_TAGGED_OFFSET.. => (first - LIST_UNTAGGED_LIMIT) as usize + 1,
+        }
+    }
+
+    /// Parses the size description at the beginning of `bytes`, located at position `at` of the
+    /// original input. Rejects descriptions which are not in the canonical form.
+    pub fn decode(bytes: &[u8], at: usize) -> Result<Header, DecodingErr> {
+        if bytes.is_empty() {
+            Err(DecodingErr::Empty)?;
+        }
 
-                if bytes.len() < len_bytes + 1 {
+        let first = bytes[0];
+        let header_len = Self::size_of(first);
+        let is_list = first >= LIST_OFFSET;
+
+        let len = match first {
+            ..=UNTAGGED_LIMIT => 1,
+            BYTE_ARRAY_OFFSET..=BYTE_ARRAY_UNTAGGED_LIMIT => (first - BYTE_ARRAY_OFFSET) as usize,
+            LIST_OFFSET..=LIST_UNTAGGED_LIMIT => (first - LIST_OFFSET) as usize,
+            _ => {
+                if bytes.len() < header_len {
                     Err(DecodingErr::SizeOverflow {
                         position: at,
-                        expected: len_bytes,
+                        expected: header_len,
                         actual: bytes.len(),
                     })?
                 }
 
-                if bytes[1] == 0 {
+                let size_bytes = &bytes[1..header_len];
+                if size_bytes[0] == 0 {
                     Err(DecodingErr::LeadingZerosInSize { position: at + 1 })?
                 }
-
-                let len: usize = bytes_to_size(bytes[1..len_bytes + 1].to_vec());
-                (
-                    RlpItem::ByteArray(bytes[len_bytes + 1..len_bytes + len + 1].to_vec()),
-                    &bytes[len_bytes + len + 1..],
-                )
-            }
-            LIST_OFFSET..=LIST_UNTAGGED_LIMIT => {
-                let len: usize = (bytes[0] - LIST_OFFSET) as usize;
-                let rest = &bytes[len + 1..];
-                let list_bytes = &bytes[1..len + 1];
-                let items = Self::decode_list_at(list_bytes, at + 1)?;
-                (RlpItem::List(items), rest)
-            }
-            LIST_TAGGED_OFFSET.. => {
-                let len_bytes: usize = (bytes[0] - LIST_UNTAGGED_LIMIT) as usize;
-                if bytes[1] == 0 {
-                    Err(DecodingErr::LeadingZerosInSize { position: at + 1 })?
+                if size_bytes.len() > std::mem::size_of::<usize>() {
+                    Err(DecodingErr::SizeTooLarge { position: at + 1 })?
                 }
 
-                let len: usize = bytes_to_size(bytes[1..len_bytes + 1].to_vec());
-                let rest = &bytes[1 + len_bytes + len..];
-                let list_bytes = &bytes[1 + len_bytes..1 + len_bytes + len];
-
-                let items = Self::decode_list_at(list_bytes, at + 1)?;
-                (RlpItem::List(items), rest)
+                let len = bytes_to_size(size_bytes);
+                if len <= UNTAGGED_SIZE_LIMIT as usize {
+                    Err(DecodingErr::NonCanonicalSize { position: at })?
+                }
+                len
             }
         };
 
-        Ok(res)
+        Ok(Header {
+            is_list,
+            header_len,
+            len,
+        })
     }
 
-    fn decode_list_at(mut bytes: &[u8], mut at: usize) -> Result<Vec<RlpItem>, DecodingErr> {
-        let mut items = Vec::new();
-        while !bytes.is_empty() {
-            let (item, rest) = Self::try_decode_at(bytes, at)?;
-            items.push(item);
-            at += (bytes.len() + 1) - rest.len();
-            bytes = rest;
+    /// Size of the entire encoded item.
+    pub fn total_len(&self) -> usize {
+        self.header_len.saturating_add(self.len)
+    }
+}
+
+impl<'a> RlpListRef<'a> {
+    /// Iterates over the list elements, decoding them on the fly.
+    pub fn iter(&self) -> RlpListIter<'a> {
+        RlpListIter {
+            rest: self.payload,
+            at: self.at,
+            depth_left: self.depth_left,
         }
-        Ok(items)
     }
 
-    fn add_size(offset: u8, bytes: Bytes) -> Bytes {
-        if bytes.len() <= UNTAGGED_SIZE_LIMIT as usize {
-            let mut res = Vec::with_capacity(bytes.len() + 1);
-            res.push(offset + bytes.len() as u8);
-            res.extend(bytes);
-            res
-        } else {
-            let size_bytes = usize_to_min_be_bytes(bytes.len());
-            let tagged_size = (UNTAGGED_SIZE_LIMIT as usize + offset as usize + size_bytes.len())
-                .to_u8()
-                .expect("Large tagged size");
+    /// Encoded payload of the list, without the size prefix.
+    pub fn payload(&self) -> &'a [u8] {
+        self.payload
+    }
 
-            let mut res = Vec::with_capacity(bytes.len() + 5);
-            res.push(tagged_size);
-            res.extend(size_bytes);
-            res.extend(bytes);
-            res
-        }
+    /// Decodes all elements at once. Useful for positional access to fields of an object.
+    pub fn to_vec(&self) -> Result<Vec<RlpRef<'a>>, DecodingErr> {
+        self.iter().collect()
     }
 }
 
-fn bytes_to_size(mut bytes: Bytes) -> usize {
-    let total = std::mem::size_of::<usize>();
+impl<'a> IntoIterator for RlpListRef<'a> {
+    type Item = Result<RlpRef<'a>, DecodingErr>;
+    type IntoIter = RlpListIter<'a>;
 
-    bytes.reverse();
-    bytes.resize(total, 0);
-    bytes.reverse();
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
 
-    usize::from_be_bytes(bytes.try_into().unwrap())
+impl<'a> Iterator for RlpListIter<'a> {
+    type Item = Result<RlpRef<'a>, DecodingErr>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.rest.is_empty() {
+            return None;
+        }
+
+        match RlpRef::try_decode_at(self.rest, self.at, self.depth_left) {
+            Ok((item, rest)) => {
+                self.at += self.rest.len() - rest.len();
+                self.rest = rest;
+                Some(Ok(item))
+            }
+            Err(e) => {
+                self.rest = &[];
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+fn bytes_to_size(bytes: &[u8]) -> usize {
+    bytes.iter().fold(0, |acc, b| (acc << 8) | *b as usize)
 }
 
 fn usize_to_min_be_bytes(n: usize) -> Bytes {
@@ -225,8 +579,88 @@ pub enum DecodingErr {
     },
     /// Empty input.
     Empty,
+    /// A single byte below 128 is wrapped in a byte array, instead of being encoded as itself.
+    NonCanonicalSingleByte { position: usize },
+    /// Multi-byte size description of a payload which fits in a single-byte one.
+    NonCanonicalSize { position: usize },
+    /// Size description does not fit in a `usize`.
+    SizeTooLarge { position: usize },
+    /// Lists are nested deeper than [DecodingLimits::max_depth].
+    DepthLimitExceeded { position: usize },
+    /// Item is larger than [DecodingLimits::max_size].
+    SizeLimitExceeded {
+        position: usize,
+        limit: usize,
+        actual: usize,
+    },
 }
 
+impl DecodingErr {
+    /// Offset of the input at which the error was detected, if known.
+    pub fn position(&self) -> Option<usize> {
+        match self {
+            DecodingErr::Trailing { input, undecoded, .. } => Some(input.len() - undecoded.len()),
+            DecodingErr::LeadingZerosInSize { position }
+            | DecodingErr::SizeOverflow { position, .. }
+            | DecodingErr::NonCanonicalSingleByte { position }
+            | DecodingErr::NonCanonicalSize { position }
+            | DecodingErr::SizeTooLarge { position }
+            | DecodingErr::DepthLimitExceeded { position }
+            | DecodingErr::SizeLimitExceeded { position, .. } => Some(*position),
+            DecodingErr::Empty => None,
+        }
+    }
+}
+
+impl std::fmt::Display for DecodingErr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DecodingErr::Trailing { input, undecoded, .. } => write!(
+                f,
+                "{} trailing bytes after the item ending at position {}",
+                undecoded.len(),
+                input.len() - undecoded.len()
+            ),
+            DecodingErr::LeadingZerosInSize { position } => {
+                write!(f, "size description at position {position} has leading zeros")
+            }
+            DecodingErr::SizeOverflow {
+                position,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "item at position {position} takes {expected} bytes, but only {actual} are left"
+            ),
+            DecodingErr::Empty => f.write_str("empty input"),
+            DecodingErr::NonCanonicalSingleByte { position } => write!(
+                f,
+                "byte array at position {position} wraps a single byte which encodes itself"
+            ),
+            DecodingErr::NonCanonicalSize { position } => write!(
+                f,
+                "item at position {position} has a long size description for a short payload"
+            ),
+            DecodingErr::SizeTooLarge { position } => {
+                write!(f, "size description at position {position} does not fit in usize")
+            }
+            DecodingErr::DepthLimitExceeded { position } => {
+                write!(f, "list at position {position} exceeds the nesting limit")
+            }
+            DecodingErr::SizeLimitExceeded {
+                position,
+                limit,
+                actual,
+            } => write!(
+                f,
+                "item at position {position} takes {actual} bytes, over the limit of {limit}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DecodingErr {}
+
 /// Conversion to an RLP value.
 pub trait ToRlpItem {
     fn to_rlp_item(&self) -> RlpItem;
@@ -234,6 +668,37 @@ pub trait ToRlpItem {
     fn serialize_rlp(&self) -> Bytes {
         self.to_rlp_item().serialize()
     }
+
+    /// Appends the serialized value to `out`.
+    fn serialize_rlp_into(&self, out: &mut Vec<u8>) {
+        self.to_rlp_item().serialize_into(out)
+    }
+
+    /// Number of bytes taken by the serialized value. Implementations should override it if the
+    /// length can be computed without converting the value into an [RlpItem].
+    fn encoded_len(&self) -> usize {
+        self.to_rlp_item().encoded_len()
+    }
+
+    /// Number of bytes taken by a serialized sequence of values. Counterpart of
+    /// [ToRlpItem::slice_to_rlp_item].
+    #[doc(hidden)]
+    fn slice_encoded_len(values: &[Self]) -> usize
+    where
+        Self: Sized,
+    {
+        list_encoded_len(values.iter().map(|x| x.encoded_len()).sum())
+    }
+
+    /// Encodes a sequence of values. Lists are used by default, with the exception of `u8`
+    /// sequences, which are byte arrays.
+    #[doc(hidden)]
+    fn slice_to_rlp_item(values: &[Self]) -> RlpItem
+    where
+        Self: Sized,
+    {
+        RlpItem::List(values.iter().map(|x| x.to_rlp_item()).collect())
+    }
 }
 
 impl From<&dyn ToRlpItem> for RlpItem {
@@ -247,33 +712,170 @@ pub trait FromRlpItem: Sized {
     fn from_rlp_item(item: &RlpItem) -> Result<Self, error::DecodingErr>;
 
     fn deserialize_rlp(data: &[u8]) -> Result<Self, error::DecodingErr> {
-        let rlp = RlpItem::deserialize(data)
-            .map_err(|_| error::DecodingErr::InvalidRlp)?;
+        let rlp = RlpItem::deserialize(data)?;
         FromRlpItem::from_rlp_item(&rlp)
     }
+
+    /// Decodes a sequence of values. Counterpart of [ToRlpItem::slice_to_rlp_item].
+    #[doc(hidden)]
+    fn vec_from_rlp_item(item: &RlpItem) -> Result<Vec<Self>, error::DecodingErr> {
+        match item {
+            RlpItem::List(items) => items.iter().map(Self::from_rlp_item).collect(),
+            RlpItem::ByteArray(_) => Err(error::DecodingErr::InvalidList),
+        }
+    }
+}
+
+/// Conversion from a borrowed RLP value. Allows decoding objects straight from the input slice,
+/// without building an intermediate [RlpItem] tree.
+pub trait FromRlpRef<'a>: Sized {
+    fn from_rlp_ref(item: &RlpRef<'a>) -> Result<Self, error::DecodingErr>;
+
+    fn deserialize_rlp_ref(data: &'a [u8]) -> Result<Self, error::DecodingErr> {
+        let rlp = RlpRef::deserialize(data)?;
+        FromRlpRef::from_rlp_ref(&rlp)
+    }
+
+    /// Decodes a sequence of values. Counterpart of [ToRlpItem::slice_to_rlp_item].
+    #[doc(hidden)]
+    fn vec_from_rlp_ref(item: &RlpRef<'a>) -> Result<Vec<Self>, error::DecodingErr> {
+        item.list()?
+            .map(|x| Self::from_rlp_ref(&x?))
+            .collect()
+    }
 }
 
 impl ToRlpItem for RlpItem {
     fn to_rlp_item(&self) -> RlpItem {
         self.clone()
     }
+
+    fn serialize_rlp_into(&self, out: &mut Vec<u8>) {
+        self.serialize_into(out)
+    }
+
+    fn encoded_len(&self) -> usize {
+        RlpItem::encoded_len(self)
+    }
+}
+
+impl FromRlpItem for RlpItem {
+    fn from_rlp_item(item: &RlpItem) -> Result<Self, error::DecodingErr> {
+        Ok(item.clone())
+    }
+}
+
+impl<'a> FromRlpRef<'a> for RlpRef<'a> {
+    fn from_rlp_ref(item: &RlpRef<'a>) -> Result<Self, error::DecodingErr> {
+        Ok(*item)
+    }
+}
+
+impl<'a> FromRlpRef<'a> for RlpItem {
+    fn from_rlp_ref(item: &RlpRef<'a>) -> Result<Self, error::DecodingErr> {
+        Ok(item.to_item()?)
+    }
+}
+
+// Unsigned integers are encoded as minimal big-endian byte arrays. Zero is a single zero byte.
+macro_rules! impl_rlp_uint {
+    ($($t:ty),*) => {$(
+        impl ToRlpItem for $t {
+            fn to_rlp_item(&self) -> RlpItem {
+                let bytes = self.to_be_bytes();
+                let zeros = bytes.iter().take_while(|b| **b == 0).count();
+                RlpItem::ByteArray(bytes[zeros.min(bytes.len() - 1)..].to_vec())
+            }
+
+            fn encoded_len(&self) -> usize {
+                let len = std::mem::size_of::<$t>() - self.leading_zeros() as usize / 8;
+                if *self <= UNTAGGED_LIMIT as $t {
+                    1
+                } else {
+                    len + 1
+                }
+            }
+        }
+
+        impl FromRlpItem for $t {
+            fn from_rlp_item(item: &RlpItem) -> Result<Self, error::DecodingErr> {
+                match item {
+                    RlpItem::ByteArray(bytes) => <$t>::from_rlp_ref(&RlpRef::ByteArray(bytes)),
+                    RlpItem::List(_) => Err(error::DecodingErr::InvalidBinary),
+                }
+            }
+        }
+
+        impl<'a> FromRlpRef<'a> for $t {
+            fn from_rlp_ref(item: &RlpRef<'a>) -> Result<Self, error::DecodingErr> {
+                let bytes = check_uint(item.byte_array()?, std::mem::size_of::<$t>())?;
+                let mut bytes_arr = [0; std::mem::size_of::<$t>()];
+                bytes_arr[std::mem::size_of::<$t>() - bytes.len()..].copy_from_slice(bytes);
+                Ok(<$t>::from_be_bytes(bytes_arr))
+            }
+        }
+    )*};
 }
 
+impl_rlp_uint!(u16, u32, u64, u128, usize);
+
 impl ToRlpItem for u8 {
     fn to_rlp_item(&self) -> RlpItem {
-        RlpItem::ByteArray(usize_to_min_be_bytes(*self as usize))
+        RlpItem::ByteArray(vec![*self])
+    }
+
+    fn encoded_len(&self) -> usize {
+        bytes_encoded_len(&[*self])
+    }
+
+    fn slice_encoded_len(values: &[Self]) -> usize {
+        bytes_encoded_len(values)
+    }
+
+    fn slice_to_rlp_item(values: &[Self]) -> RlpItem {
+        RlpItem::ByteArray(values.to_vec())
     }
 }
 
-impl ToRlpItem for u32 {
-    fn to_rlp_item(&self) -> RlpItem {
-        RlpItem::ByteArray(usize_to_min_be_bytes(*self as usize))
+impl FromRlpItem for u8 {
+    fn from_rlp_item(item: &RlpItem) -> Result<Self, error::DecodingErr> {
+        u8::from_rlp_ref(&RlpRef::ByteArray(item.as_bytes()?))
+    }
+
+    fn vec_from_rlp_item(item: &RlpItem) -> Result<Vec<Self>, error::DecodingErr> {
+        item.byte_array()
+    }
+}
+
+impl<'a> FromRlpRef<'a> for u8 {
+    fn from_rlp_ref(item: &RlpRef<'a>) -> Result<Self, error::DecodingErr> {
+        match item.byte_array()? {
+            [b] => Ok(*b),
+            _ => Err(error::DecodingErr::InvalidInt),
+        }
+    }
+
+    fn vec_from_rlp_ref(item: &RlpRef<'a>) -> Result<Vec<Self>, error::DecodingErr> {
+        item.byte_array().map(|x| x.to_vec())
     }
 }
 
-impl ToRlpItem for usize {
+impl ToRlpItem for num::BigUint {
     fn to_rlp_item(&self) -> RlpItem {
-        RlpItem::ByteArray(usize_to_min_be_bytes(*self))
+        RlpItem::ByteArray(self.to_bytes_be())
+    }
+}
+
+impl FromRlpItem for num::BigUint {
+    fn from_rlp_item(item: &RlpItem) -> Result<Self, error::DecodingErr> {
+        num::BigUint::from_rlp_ref(&RlpRef::ByteArray(item.as_bytes()?))
+    }
+}
+
+impl<'a> FromRlpRef<'a> for num::BigUint {
+    fn from_rlp_ref(item: &RlpRef<'a>) -> Result<Self, error::DecodingErr> {
+        let bytes = check_uint(item.byte_array()?, usize::MAX)?;
+        Ok(num::BigUint::from_bytes_be(bytes))
     }
 }
 
@@ -281,93 +883,190 @@ impl ToRlpItem for bool {
     fn to_rlp_item(&self) -> RlpItem {
         RlpItem::ByteArray(vec![*self as u8])
     }
+
+    fn encoded_len(&self) -> usize {
+        1
+    }
 }
 
-impl ToRlpItem for Vec<u8> {
-    fn to_rlp_item(&self) -> RlpItem {
-        RlpItem::ByteArray(self.to_vec())
+impl FromRlpItem for bool {
+    fn from_rlp_item(item: &RlpItem) -> Result<Self, error::DecodingErr> {
+        decode_bool(item.as_bytes()?)
     }
 }
 
-impl ToRlpItem for [u8] {
-    fn to_rlp_item(&self) -> RlpItem {
-        RlpItem::ByteArray(self.to_vec())
+impl<'a> FromRlpRef<'a> for bool {
+    fn from_rlp_ref(item: &RlpRef<'a>) -> Result<Self, error::DecodingErr> {
+        decode_bool(item.byte_array()?)
     }
 }
 
-impl ToRlpItem for Vec<RlpItem> {
+impl<T: ToRlpItem> ToRlpItem for Vec<T> {
     fn to_rlp_item(&self) -> RlpItem {
-        RlpItem::List(self.iter().map(|x| x.to_rlp_item()).collect())
+        T::slice_to_rlp_item(self)
+    }
+
+    fn encoded_len(&self) -> usize {
+        T::slice_encoded_len(self)
     }
 }
 
-impl ToRlpItem for [RlpItem] {
+impl<T: ToRlpItem> ToRlpItem for [T] {
     fn to_rlp_item(&self) -> RlpItem {
-        RlpItem::List(self.iter().map(|x| x.to_rlp_item()).collect())
+        T::slice_to_rlp_item(self)
     }
-}
 
-impl FromRlpItem for RlpItem {
-    fn from_rlp_item(item: &RlpItem) -> Result<Self, error::DecodingErr> {
-        Ok(item.clone())
+    fn encoded_len(&self) -> usize {
+        T::slice_encoded_len(self)
     }
 }
 
-impl FromRlpItem for u8 {
+impl<T: FromRlpItem> FromRlpItem for Vec<T> {
     fn from_rlp_item(item: &RlpItem) -> Result<Self, error::DecodingErr> {
-        let bytes = item.byte_array()?;
+        T::vec_from_rlp_item(item)
+    }
+}
 
-        if bytes.len() != 1 {
-            Err(error::DecodingErr::InvalidInt)?;
-        }
+impl<'a, T: FromRlpRef<'a>> FromRlpRef<'a> for Vec<T> {
+    fn from_rlp_ref(item: &RlpRef<'a>) -> Result<Self, error::DecodingErr> {
+        T::vec_from_rlp_ref(item)
+    }
+}
 
-        Ok(bytes[0])
+impl<'a> FromRlpRef<'a> for &'a [u8] {
+    fn from_rlp_ref(item: &RlpRef<'a>) -> Result<Self, error::DecodingErr> {
+        item.byte_array()
     }
 }
 
-impl FromRlpItem for u32 {
-    fn from_rlp_item(item: &RlpItem) -> Result<Self, error::DecodingErr> {
-        let bytes = item.byte_array()?;
-        let size = std::mem::size_of::<Self>();
+impl<const N: usize> ToRlpItem for [u8; N] {
+    fn to_rlp_item(&self) -> RlpItem {
+        RlpItem::ByteArray(self.to_vec())
+    }
 
-        if bytes.is_empty() || bytes.len() > size || (bytes.len() > 1 && bytes[0] == 0) {
-            Err(error::DecodingErr::InvalidInt)?;
-        }
+    fn encoded_len(&self) -> usize {
+        bytes_encoded_len(self)
+    }
+}
 
-        let mut bytes_vec = vec![0; size - bytes.len()];
-        bytes_vec.extend(bytes);
+impl<const N: usize> FromRlpItem for [u8; N] {
+    fn from_rlp_item(item: &RlpItem) -> Result<Self, error::DecodingErr> {
+        <[u8; N]>::from_rlp_ref(&RlpRef::ByteArray(item.as_bytes()?))
+    }
+}
 
-        let bytes_arr = bytes_vec
+impl<'a, const N: usize> FromRlpRef<'a> for [u8; N] {
+    fn from_rlp_ref(item: &RlpRef<'a>) -> Result<Self, error::DecodingErr> {
+        item.byte_array()?
             .try_into()
-            .or(Err(error::DecodingErr::InvalidInt))?;
+            .map_err(|_| error::DecodingErr::IncorrectSize)
+    }
+}
+
+/// Optional values follow the protocol convention of encoding [None] as an empty byte array.
+/// Thus, [Some] must not hold a value which is itself encoded as an empty byte array.
+impl<T: ToRlpItem> ToRlpItem for Option<T> {
+    fn to_rlp_item(&self) -> RlpItem {
+        match self {
+            Some(x) => x.to_rlp_item(),
+            None => RlpItem::ByteArray(vec![]),
+        }
+    }
 
-        Ok(Self::from_be_bytes(bytes_arr))
+    fn encoded_len(&self) -> usize {
+        match self {
+            Some(x) => x.encoded_len(),
+            None => 1,
+        }
     }
 }
 
-impl FromRlpItem for bool {
+impl<T: FromRlpItem> FromRlpItem for Option<T> {
     fn from_rlp_item(item: &RlpItem) -> Result<Self, error::DecodingErr> {
-        let bytes = item.byte_array()?;
+        match item {
+            RlpItem::ByteArray(bytes) if bytes.is_empty() => Ok(None),
+            _ => T::from_rlp_item(item).map(Some),
+        }
+    }
+}
 
-        if *bytes == vec![0u8] {
-            Ok(false)
-        } else if *bytes == vec![1u8] {
-            Ok(true)
-        } else {
-            Err(error::DecodingErr::InvalidBool)
+impl<'a, T: FromRlpRef<'a>> FromRlpRef<'a> for Option<T> {
+    fn from_rlp_ref(item: &RlpRef<'a>) -> Result<Self, error::DecodingErr> {
+        match item {
+            RlpRef::ByteArray([]) => Ok(None),
+            _ => T::from_rlp_ref(item).map(Some),
         }
     }
 }
 
-impl FromRlpItem for Vec<u8> {
-    fn from_rlp_item(item: &RlpItem) -> Result<Self, error::DecodingErr> {
-        item.byte_array()
+// Tuples are encoded as lists of their elements.
+macro_rules! impl_rlp_tuple {
+    ($len:literal; $($t:ident $i:tt),+) => {
+        impl<$($t: ToRlpItem),+> ToRlpItem for ($($t,)+) {
+            fn to_rlp_item(&self) -> RlpItem {
+                RlpItem::List(vec![$(self.$i.to_rlp_item()),+])
+            }
+
+            fn encoded_len(&self) -> usize {
+                list_encoded_len(0 $(+ self.$i.encoded_len())+)
+            }
+        }
+
+        impl<$($t: FromRlpItem),+> FromRlpItem for ($($t,)+) {
+            fn from_rlp_item(item: &RlpItem) -> Result<Self, error::DecodingErr> {
+                let items = item.as_list()?;
+                if items.len() != $len {
+                    Err(error::DecodingErr::InvalidObject {
+                        object: "tuple",
+                        fields: $len,
+                    })?
+                }
+                Ok(($($t::from_rlp_item(&items[$i])?,)+))
+            }
+        }
+
+        impl<'a, $($t: FromRlpRef<'a>),+> FromRlpRef<'a> for ($($t,)+) {
+            fn from_rlp_ref(item: &RlpRef<'a>) -> Result<Self, error::DecodingErr> {
+                let items = item.list()?.collect::<Result<Vec<_>, _>>()?;
+                if items.len() != $len {
+                    Err(error::DecodingErr::InvalidObject {
+                        object: "tuple",
+                        fields: $len,
+                    })?
+                }
+                Ok(($($t::from_rlp_ref(&items[$i])?,)+))
+            }
+        }
+    };
+}
+
+impl_rlp_tuple!(1; A 0);
+impl_rlp_tuple!(2; A 0, B 1);
+impl_rlp_tuple!(3; A 0, B 1, C 2);
+impl_rlp_tuple!(4; A 0, B 1, C 2, D 3);
+impl_rlp_tuple!(5; A 0, B 1, C 2, D 3, E 4);
+impl_rlp_tuple!(6; A 0, B 1, C 2, D 3, E 4, F 5);
+impl_rlp_tuple!(7; A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+impl_rlp_tuple!(8; A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
+impl_rlp_tuple!(9; A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8);
+impl_rlp_tuple!(10; A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9);
+impl_rlp_tuple!(11; A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10);
+impl_rlp_tuple!(12; A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11);
+
+/// Checks that `bytes` is a minimal big-endian encoding of an integer of at most `max_len` bytes.
+fn check_uint(bytes: &[u8], max_len: usize) -> Result<&[u8], error::DecodingErr> {
+    if bytes.is_empty() || bytes.len() > max_len || (bytes.len() > 1 && bytes[0] == 0) {
+        Err(error::DecodingErr::InvalidInt)?;
     }
+
+    Ok(bytes)
 }
 
-impl FromRlpItem for Vec<RlpItem> {
-    fn from_rlp_item(item: &RlpItem) -> Result<Self, error::DecodingErr> {
-        item.list()
+fn decode_bool(bytes: &[u8]) -> Result<bool, error::DecodingErr> {
+    match bytes {
+        [0] => Ok(false),
+        [1] => Ok(true),
+        _ => Err(error::DecodingErr::InvalidBool),
     }
 }
 
@@ -423,6 +1122,28 @@ mod tests {
             prop_assert_eq!(rlp, d);
         }
 
+        #[test]
+        fn serialize_into_appends(rlp: RlpItem, prefix: Vec<u8>) {
+            let mut out = prefix.clone();
+            rlp.serialize_into(&mut out);
+            let e = rlp.serialize();
+            prop_assert_eq!(e.len(), rlp.encoded_len());
+            prop_assert_eq!(out, [prefix, e].concat());
+        }
+
+        #[test]
+        fn arbitrary_input(input in any_u8vec(0u8, 64u8)) {
+            // Malformed input must result in an error, never in a panic.
+            let _ = RlpItem::deserialize(&input);
+        }
+
+        #[test]
+        fn encode_decode_ref(rlp: RlpItem) {
+            let e = rlp.serialize();
+            let d = RlpRef::deserialize(&e).expect("decoding failed");
+            prop_assert_eq!(d.to_item(), Ok(rlp));
+        }
+
         #[test]
         fn one_byte(b in 0..=UNTAGGED_LIMIT) {
             let input = RlpItem::ByteArray(vec![b]);
@@ -546,4 +1267,402 @@ mod tests {
             Err(DecodingErr::LeadingZerosInSize { position: 1 })
         );
     }
+
+    #[test]
+    fn ref_borrows_input() {
+        let input = RlpItem::List(vec![
+            RlpItem::ByteArray(vec![1, 2, 3]),
+            RlpItem::List(vec![]),
+        ])
+        .serialize();
+
+        let rlp = RlpRef::deserialize(&input).expect("decoding failed");
+        let items = rlp.list().unwrap().collect::<Result<Vec<_>, _>>().unwrap();
+        let bytes = items[0].byte_array().unwrap();
+
+        assert_eq!(bytes, &[1, 2, 3]);
+        assert!(std::ptr::eq(bytes.as_ptr(), input[2..].as_ptr()));
+        assert_eq!(items[1].list().unwrap().count(), 0);
+    }
+
+    #[test]
+    fn truncated_list() {
+        let input = vec![LIST_OFFSET + 3, 1, 2];
+        assert_eq!(
+            RlpItem::deserialize(&input),
+            Err(DecodingErr::SizeOverflow {
+                position: 0,
+                expected: 4,
+                actual: 3
+            })
+        );
+    }
+
+    #[test]
+    fn non_canonical_single_byte() {
+        let input = vec![LIST_OFFSET + 2, BYTE_ARRAY_OFFSET + 1, UNTAGGED_LIMIT];
+        assert_eq!(
+            RlpItem::deserialize(&input),
+            Err(DecodingErr::NonCanonicalSingleByte { position: 1 })
+        );
+
+        let input = vec![BYTE_ARRAY_OFFSET + 1, UNTAGGED_LIMIT + 1];
+        assert!(RlpItem::deserialize(&input).is_ok());
+    }
+
+    #[test]
+    fn non_canonical_size() {
+        let mut input = vec![BYTE_ARRAY_TAGGED_OFFSET, UNTAGGED_SIZE_LIMIT];
+        input.extend([1; UNTAGGED_SIZE_LIMIT as usize]);
+        assert_eq!(
+            RlpItem::deserialize(&input),
+            Err(DecodingErr::NonCanonicalSize { position: 0 })
+        );
+
+        let input = vec![LIST_TAGGED_OFFSET, 1, LIST_OFFSET];
+        assert_eq!(
+            RlpItem::deserialize(&input),
+            Err(DecodingErr::NonCanonicalSize { position: 0 })
+        );
+    }
+
+    #[test]
+    fn truncated_size() {
+        let input = vec![BYTE_ARRAY_TAGGED_OFFSET + 2, 1];
+        assert_eq!(
+            RlpItem::deserialize(&input),
+            Err(DecodingErr::SizeOverflow {
+                position: 0,
+                expected: 4,
+                actual: 2
+            })
+        );
+    }
+
+    #[test]
+    fn huge_declared_size() {
+        let input = vec![BYTE_ARRAY_LIMIT, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff];
+        assert_eq!(
+            RlpItem::deserialize(&input),
+            Err(DecodingErr::SizeOverflow {
+                position: 0,
+                expected: usize::MAX,
+                actual: 9
+            })
+        );
+    }
+
+    #[test]
+    fn depth_limit() {
+        let nested = |depth| {
+            (0..depth).fold(RlpItem::ByteArray(vec![]), |acc, _| RlpItem::List(vec![acc]))
+        };
+        let limits = DecodingLimits {
+            max_depth: 3,
+            ..Default::default()
+        };
+
+        let input = nested(3).serialize();
+        assert_eq!(
+            RlpItem::deserialize_with_limits(&input, &limits),
+            Ok(nested(3))
+        );
+
+        let input = nested(4).serialize();
+        assert_eq!(
+            RlpItem::deserialize_with_limits(&input, &limits),
+            Err(DecodingErr::DepthLimitExceeded { position: 3 })
+        );
+
+        let input = nested(DecodingLimits::DEFAULT_MAX_DEPTH + 1).serialize();
+        assert!(matches!(
+            RlpItem::deserialize(&input),
+            Err(DecodingErr::DepthLimitExceeded { .. })
+        ));
+    }
+
+    #[test]
+    fn size_limit() {
+        let limits = DecodingLimits {
+            max_size: 10,
+            ..Default::default()
+        };
+
+        let input = RlpItem::ByteArray(vec![200; 9]).serialize();
+        assert!(RlpItem::deserialize_with_limits(&input, &limits).is_ok());
+
+        let input = RlpItem::ByteArray(vec![200; 10]).serialize();
+        assert_eq!(
+            RlpItem::deserialize_with_limits(&input, &limits),
+            Err(DecodingErr::SizeLimitExceeded {
+                position: 0,
+                limit: 10,
+                actual: 11
+            })
+        );
+    }
+
+    #[derive(Debug, PartialEq, Default, ToRlpItem, FromRlpItem, FromRlpRef)]
+    struct Inner(u8, bool);
+
+    #[derive(Debug, PartialEq, ToRlpItem, FromRlpItem, FromRlpRef)]
+    #[rlp(tag = 42u8, vsn = 1u8)]
+    struct Outer {
+        nested: Inner,
+        #[rlp(bytes)]
+        serialized: Inner,
+        #[rlp(skip)]
+        skipped: Inner,
+        payload: Bytes,
+    }
+
+    #[test]
+    fn derive_roundtrip() {
+        let input = Outer {
+            nested: Inner(7, true),
+            serialized: Inner(8, false),
+            skipped: Inner::default(),
+            payload: vec![1, 2, 3],
+        };
+        let expect = RlpItem::List(vec![
+            RlpItem::ByteArray(vec![42]),
+            RlpItem::ByteArray(vec![1]),
+            RlpItem::List(vec![RlpItem::ByteArray(vec![7]), RlpItem::ByteArray(vec![1])]),
+            RlpItem::ByteArray(vec![0xc2, 8, 0]),
+            RlpItem::ByteArray(vec![1, 2, 3]),
+        ]);
+
+        let encoded = input.serialize_rlp();
+        assert_eq!(input.to_rlp_item(), expect);
+        assert_eq!(Outer::deserialize_rlp(&encoded), Ok(input));
+        assert_eq!(Outer::deserialize_rlp_ref(&encoded), Outer::deserialize_rlp(&encoded));
+    }
+
+    #[test]
+    fn derive_errors() {
+        let wrong_tag = RlpItem::List(vec![
+            RlpItem::ByteArray(vec![43]),
+            RlpItem::ByteArray(vec![1]),
+            Inner(7, true).to_rlp_item(),
+            RlpItem::ByteArray(Inner(8, false).serialize_rlp()),
+            RlpItem::ByteArray(vec![]),
+        ]);
+        assert_eq!(
+            Outer::from_rlp_item(&wrong_tag),
+            Err(error::DecodingErr::InvalidTag { object: "Outer" })
+        );
+
+        let wrong_field = RlpItem::List(vec![
+            RlpItem::ByteArray(vec![42]),
+            RlpItem::ByteArray(vec![1]),
+            RlpItem::List(vec![RlpItem::ByteArray(vec![7]), RlpItem::ByteArray(vec![2])]),
+            RlpItem::ByteArray(Inner(8, false).serialize_rlp()),
+            RlpItem::ByteArray(vec![]),
+        ]);
+        let expect = Err(error::DecodingErr::InvalidField {
+            object: "Outer",
+            field: "nested",
+            err: Box::new(error::DecodingErr::InvalidField {
+                object: "Inner",
+                field: "1",
+                err: Box::new(error::DecodingErr::InvalidBool),
+            }),
+        });
+        assert_eq!(Outer::from_rlp_item(&wrong_field), expect);
+        assert_eq!(Outer::deserialize_rlp_ref(&wrong_field.serialize()), expect);
+
+        assert_eq!(
+            Inner::from_rlp_item(&RlpItem::List(vec![])),
+            Err(error::DecodingErr::InvalidObject {
+                object: "Inner",
+                fields: 2
+            })
+        );
+    }
+
+    fn value_roundtrip<T>(value: T)
+    where
+        T: ToRlpItem + FromRlpItem + for<'a> FromRlpRef<'a> + PartialEq + std::fmt::Debug,
+    {
+        let encoded = value.serialize_rlp();
+        assert_eq!(value.encoded_len(), encoded.len());
+        assert_eq!(T::deserialize_rlp(&encoded).as_ref(), Ok(&value));
+        assert_eq!(T::deserialize_rlp_ref(&encoded), Ok(value));
+    }
+
+    proptest! {
+        #[test]
+        fn uint_roundtrip(a: u16, b: u32, c: u64, d: u128, e: usize) {
+            value_roundtrip(a);
+            value_roundtrip(b);
+            value_roundtrip(c);
+            value_roundtrip(d);
+            value_roundtrip(e);
+        }
+
+        #[test]
+        fn uint_minimal(n: u64) {
+            let bytes = n.to_rlp_item().byte_array().unwrap();
+            prop_assert_eq!(bytes.len(), (n.checked_ilog(256).unwrap_or(0) + 1) as usize);
+            prop_assert_eq!(num::BigUint::from(n).to_rlp_item(), n.to_rlp_item());
+        }
+
+        #[test]
+        fn collections_roundtrip(xs: Vec<u64>, bytes: Vec<u8>, opt: Option<u32>) {
+            value_roundtrip(xs.clone());
+            value_roundtrip(bytes.clone());
+            value_roundtrip(opt);
+            value_roundtrip((xs, bytes, opt, true));
+        }
+    }
+
+    #[test]
+    fn uint_errors() {
+        let decode = |bytes: &[u8]| u64::from_rlp_item(&RlpItem::ByteArray(bytes.to_vec()));
+        assert_eq!(decode(&[0]), Ok(0));
+        assert_eq!(decode(&[]), Err(error::DecodingErr::InvalidInt));
+        assert_eq!(decode(&[0, 1]), Err(error::DecodingErr::InvalidInt));
+        assert_eq!(decode(&[1; 9]), Err(error::DecodingErr::InvalidInt));
+        assert_eq!(
+            u64::from_rlp_item(&RlpItem::List(vec![])),
+            Err(error::DecodingErr::InvalidBinary)
+        );
+        assert_eq!(
+            u16::deserialize_rlp_ref(&[BYTE_ARRAY_OFFSET + 3, 1, 0, 0]),
+            Err(error::DecodingErr::InvalidInt)
+        );
+        assert_eq!(
+            num::BigUint::from_rlp_item(&RlpItem::ByteArray(vec![0, 1])),
+            Err(error::DecodingErr::InvalidInt)
+        );
+    }
+
+    #[test]
+    fn big_uint() {
+        let n = num::BigUint::from(u128::MAX) * 1000u32;
+        let item = n.to_rlp_item();
+        assert_eq!(item.byte_array().unwrap().len(), 18);
+        value_roundtrip(n);
+        value_roundtrip(num::BigUint::from(0u8));
+    }
+
+    #[test]
+    fn fixed_arrays() {
+        value_roundtrip([7u8; 32]);
+        assert_eq!(
+            <[u8; 32]>::from_rlp_item(&RlpItem::ByteArray(vec![7; 31])),
+            Err(error::DecodingErr::IncorrectSize)
+        );
+    }
+
+    #[test]
+    fn options() {
+        assert_eq!(None::<u64>.serialize_rlp(), vec![BYTE_ARRAY_OFFSET]);
+        assert_eq!(Some(1u64).serialize_rlp(), vec![1]);
+        value_roundtrip(Some(vec![1u64, 2, 3]));
+        value_roundtrip(None::<Vec<u64>>);
+    }
+
+    #[test]
+    fn tuples() {
+        value_roundtrip((1u8,));
+        value_roundtrip((
+            1u8,
+            2u16,
+            3u32,
+            4u64,
+            5u128,
+            6usize,
+            true,
+            vec![8u8],
+            [9u8; 2],
+            None::<u8>,
+            (11u8, 12u8),
+            13u8,
+        ));
+        assert_eq!(
+            <(u8, u8)>::from_rlp_item(&RlpItem::List(vec![RlpItem::ByteArray(vec![1])])),
+            Err(error::DecodingErr::InvalidObject {
+                object: "tuple",
+                fields: 2
+            })
+        );
+    }
+
+    #[test]
+    fn accessors() {
+        let item: RlpItem = "[1, [0x0203, [7, 0x]], 0x05]".parse().unwrap();
+
+        assert_eq!(item.as_list().map(|x| x.len()), Ok(3));
+        assert_eq!(item.at(&[1, 0]).and_then(|x| x.as_bytes()), Ok(&[2, 3][..]));
+        assert_eq!(item.at(&[]), Ok(&item));
+        assert_eq!(item.get::<u64>(0), Ok(1));
+        assert_eq!(item.get_at::<u16>(&[1, 0]), Ok(0x0203));
+        assert_eq!(item.get_at::<Option<u8>>(&[1, 1, 1]), Ok(None));
+        assert_eq!(
+            item.as_bytes(),
+            Err(error::DecodingErr::InvalidBinary)
+        );
+    }
+
+    #[test]
+    fn path_errors() {
+        let item: RlpItem = "[1, [0x0203, [7, 0x]], 0x05]".parse().unwrap();
+        let fail = |path: &[usize], err| error::DecodingErr::InvalidPath {
+            path: path.to_vec(),
+            err: Box::new(err),
+        };
+
+        assert_eq!(
+            item.at(&[1, 5, 0]),
+            Err(fail(&[1, 5], error::DecodingErr::IndexOutOfBounds { index: 5, len: 2 }))
+        );
+        assert_eq!(
+            item.at(&[2, 0]),
+            Err(fail(&[2, 0], error::DecodingErr::InvalidList))
+        );
+        assert_eq!(
+            item.get_at::<bool>(&[1, 1, 0]),
+            Err(fail(&[1, 1, 0], error::DecodingErr::InvalidBool))
+        );
+        assert_eq!(item.get::<u8>(1), Err(fail(&[1], error::DecodingErr::InvalidBinary)));
+    }
+
+    #[test]
+    fn error_context() {
+        use std::error::Error;
+
+        let truncated = [LIST_OFFSET + 3, 1, 2];
+        let err = u8::deserialize_rlp(&truncated).unwrap_err();
+        assert_eq!(
+            err,
+            error::DecodingErr::InvalidRlp(DecodingErr::SizeOverflow {
+                position: 0,
+                expected: 4,
+                actual: 3
+            })
+        );
+        assert_eq!(err.to_string(), "malformed RLP");
+        assert_eq!(
+            err.source().map(|e| e.to_string()),
+            Some("item at position 0 takes 4 bytes, but only 3 are left".to_string())
+        );
+
+        let wrong_field = "[42, 1, [7, 2], 0xc20800, 0x]".parse::<RlpItem>().unwrap();
+        let err = Outer::from_rlp_item(&wrong_field).unwrap_err();
+        let mut chain = vec![err.to_string()];
+        let mut source = err.source();
+        while let Some(e) = source {
+            chain.push(e.to_string());
+            source = e.source();
+        }
+        assert_eq!(
+            chain,
+            [
+                "invalid field `nested` of Outer",
+                "invalid field `1` of Inner",
+                "expected a bool encoded as 0 or 1"
+            ]
+        );
+    }
 }
@@ -0,0 +1,541 @@
+//! Serde deserializer reading RLP. Counterpart of the [serializer](super::ser), decoding values
+//! straight from the input without building an intermediate [RlpItem] tree. Strings and byte
+//! buffers can be borrowed from the input.
+
+use serde::de::{self, DeserializeSeed, IntoDeserializer, Visitor};
+use serde::Deserialize;
+
+use super::*;
+
+impl de::Error for SerdeErr {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        SerdeErr::Message(msg.to_string())
+    }
+}
+
+/// Deserializes a serde data structure from RLP. Requires consuming the entire input.
+pub fn from_bytes<'de, T: Deserialize<'de>>(bytes: &'de [u8]) -> Result<T, SerdeErr> {
+    let item = RlpRef::deserialize(bytes).map_err(SerdeErr::Rlp)?;
+    T::deserialize(Deserializer::new(item))
+}
+
+/// Serde deserializer reading a single [RlpRef].
+pub struct Deserializer<'de> {
+    item: RlpRef<'de>,
+}
+
+impl<'de> Deserializer<'de> {
+    pub fn new(item: RlpRef<'de>) -> Self {
+        Deserializer { item }
+    }
+
+    fn value<T: FromRlpRef<'de>>(&self) -> Result<T, SerdeErr> {
+        T::from_rlp_ref(&self.item).map_err(SerdeErr::Decoding)
+    }
+
+    fn bytes(&self) -> Result<&'de [u8], SerdeErr> {
+        self.item.byte_array().map_err(SerdeErr::Decoding)
+    }
+
+    fn str(&self) -> Result<&'de str, SerdeErr> {
+        std::str::from_utf8(self.bytes()?)
+            .map_err(|_| SerdeErr::Decoding(error::DecodingErr::InvalidEncoding))
+    }
+
+    fn list(&self) -> Result<ListAccess<'de>, SerdeErr> {
+        let iter = self.item.list().map_err(SerdeErr::Decoding)?;
+        Ok(ListAccess { iter, value: None })
+    }
+
+    fn signed<T: TryFrom<u128>>(&self) -> Result<T, SerdeErr> {
+        T::try_from(self.value::<u128>()?)
+            .map_err(|_| SerdeErr::Decoding(error::DecodingErr::InvalidInt))
+    }
+}
+
+/// Elements of an RLP list, handed over to serde as a sequence or a map of key-value pairs.
+struct ListAccess<'de> {
+    iter: RlpListIter<'de>,
+    /// Value of the map entry whose key was just deserialized.
+    value: Option<RlpRef<'de>>,
+}
+
+impl<'de> ListAccess<'de> {
+    fn next_item(&mut self) -> Result<Option<RlpRef<'de>>, SerdeErr> {
+        self.iter.next().transpose().map_err(SerdeErr::Rlp)
+    }
+
+    /// Checks that all elements have been consumed.
+    fn finish<T>(mut self, value: T) -> Result<T, SerdeErr> {
+        match self.next_item()? {
+            None => Ok(value),
+            Some(_) => Err(SerdeErr::TrailingElements),
+        }
+    }
+
+    fn visit<V: Visitor<'de>>(mut self, visitor: V) -> Result<V::Value, SerdeErr> {
+        let value = visitor.visit_seq(&mut self)?;
+        self.finish(value)
+    }
+}
+
+impl<'de> de::SeqAccess<'de> for ListAccess<'de> {
+    type Error = SerdeErr;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, SerdeErr> {
+        match self.next_item()? {
+            Some(item) => seed.deserialize(Deserializer::new(item)).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+impl<'de> de::MapAccess<'de> for ListAccess<'de> {
+    type Error = SerdeErr;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, SerdeErr> {
+        let entry = match self.next_item()? {
+            Some(entry) => entry,
+            None => return Ok(None),
+        };
+
+        let invalid = || {
+            SerdeErr::Decoding(error::DecodingErr::InvalidObject {
+                object: "map entry",
+                fields: 2,
+            })
+        };
+        let mut fields = entry.list().map_err(|_| invalid())?;
+        let (key, value) = match (fields.next(), fields.next(), fields.next()) {
+            (Some(key), Some(value), None) => {
+                (key.map_err(SerdeErr::Rlp)?, value.map_err(SerdeErr::Rlp)?)
+            }
+            _ => Err(invalid())?,
+        };
+
+        self.value = Some(value);
+        seed.deserialize(Deserializer::new(key)).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, SerdeErr> {
+        let value = self
+            .value
+            .take()
+            .ok_or_else(|| SerdeErr::Message("map value requested before its key".to_string()))?;
+        seed.deserialize(Deserializer::new(value))
+    }
+}
+
+/// Enum variant, along with the list elements following its index, if any.
+pub struct VariantAccess<'de> {
+    fields: Option<ListAccess<'de>>,
+}
+
+impl<'de> de::EnumAccess<'de> for Deserializer<'de> {
+    type Error = SerdeErr;
+    type Variant = VariantAccess<'de>;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, VariantAccess<'de>), SerdeErr> {
+        let (index, fields) = match self.item {
+            RlpRef::ByteArray(_) => (self.value::<u32>()?, None),
+            RlpRef::List(_) => {
+                let mut fields = self.list()?;
+                let index = fields
+                    .next_item()?
+                    .ok_or(SerdeErr::Decoding(error::DecodingErr::InvalidList))?;
+                (Deserializer::new(index).value::<u32>()?, Some(fields))
+            }
+        };
+
+        let variant = seed.deserialize(IntoDeserializer::<SerdeErr>::into_deserializer(index))?;
+        Ok((variant, VariantAccess { fields }))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for VariantAccess<'de> {
+    type Error = SerdeErr;
+
+    fn unit_variant(self) -> Result<(), SerdeErr> {
+        match self.fields {
+            None => Ok(()),
+            Some(_) => Err(SerdeErr::Decoding(error::DecodingErr::InvalidBinary)),
+        }
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, SerdeErr> {
+        let mut fields = self.fields()?;
+        let field =
+            fields
+                .next_item()?
+                .ok_or(SerdeErr::Decoding(error::DecodingErr::InvalidObject {
+                    object: "newtype variant",
+                    fields: 2,
+                }))?;
+        let value = seed.deserialize(Deserializer::new(field))?;
+        fields.finish(value)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, SerdeErr> {
+        self.fields()?.visit(visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SerdeErr> {
+        self.fields()?.visit(visitor)
+    }
+}
+
+impl<'de> VariantAccess<'de> {
+    fn fields(self) -> Result<ListAccess<'de>, SerdeErr> {
+        self.fields
+            .ok_or(SerdeErr::Decoding(error::DecodingErr::InvalidList))
+    }
+}
+
+impl<'de> de::Deserializer<'de> for Deserializer<'de> {
+    type Error = SerdeErr;
+
+    /// RLP does not describe the types of values, so only the shape of the input is available:
+    /// byte arrays are visited as bytes, and lists as sequences.
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeErr> {
+        match self.item {
+            RlpRef::ByteArray(bytes) => visitor.visit_borrowed_bytes(bytes),
+            RlpRef::List(_) => self.list()?.visit(visitor),
+        }
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeErr> {
+        visitor.visit_bool(self.value()?)
+    }
+
+    fn deserialize_i8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeErr> {
+        visitor.visit_i8(self.signed()?)
+    }
+
+    fn deserialize_i16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeErr> {
+        visitor.visit_i16(self.signed()?)
+    }
+
+    fn deserialize_i32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeErr> {
+        visitor.visit_i32(self.signed()?)
+    }
+
+    fn deserialize_i64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeErr> {
+        visitor.visit_i64(self.signed()?)
+    }
+
+    fn deserialize_i128<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeErr> {
+        visitor.visit_i128(self.signed()?)
+    }
+
+    fn deserialize_u8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeErr> {
+        visitor.visit_u8(self.value()?)
+    }
+
+    fn deserialize_u16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeErr> {
+        visitor.visit_u16(self.value()?)
+    }
+
+    fn deserialize_u32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeErr> {
+        visitor.visit_u32(self.value()?)
+    }
+
+    fn deserialize_u64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeErr> {
+        visitor.visit_u64(self.value()?)
+    }
+
+    fn deserialize_u128<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeErr> {
+        visitor.visit_u128(self.value()?)
+    }
+
+    fn deserialize_f32<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, SerdeErr> {
+        Err(SerdeErr::Unsupported("floating point number"))
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, SerdeErr> {
+        Err(SerdeErr::Unsupported("floating point number"))
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeErr> {
+        let mut chars = self.str()?.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => visitor.visit_char(c),
+            _ => Err(SerdeErr::Decoding(error::DecodingErr::InvalidEncoding)),
+        }
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeErr> {
+        visitor.visit_borrowed_str(self.str()?)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeErr> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeErr> {
+        visitor.visit_borrowed_bytes(self.bytes()?)
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeErr> {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeErr> {
+        match self.item {
+            RlpRef::ByteArray([]) => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeErr> {
+        self.list()?.finish(())?;
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, SerdeErr> {
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, SerdeErr> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeErr> {
+        self.list()?.visit(visitor)
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, SerdeErr> {
+        self.list()?.visit(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, SerdeErr> {
+        self.list()?.visit(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeErr> {
+        let mut entries = self.list()?;
+        let value = visitor.visit_map(&mut entries)?;
+        entries.finish(value)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SerdeErr> {
+        self.list()?.visit(visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SerdeErr> {
+        visitor.visit_enum(self)
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeErr> {
+        self.deserialize_u32(visitor)
+    }
+
+    /// Walks the whole item, so that a malformed one is still reported.
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeErr> {
+        self.deserialize_any(visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use serde::Serialize;
+    use std::collections::BTreeMap;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Account {
+        #[serde(with = "serde_bytes")]
+        pubkey: Vec<u8>,
+        nonce: u64,
+        balance: u128,
+        flags: Option<u32>,
+        name: String,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Event {
+        Ping,
+        Height(u64),
+        Move(u8, u8),
+        Rename { from: String, to: String },
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Borrowed<'a> {
+        #[serde(borrow, with = "serde_bytes")]
+        payload: &'a [u8],
+        label: &'a str,
+    }
+
+    fn roundtrip<T>(value: T)
+    where
+        T: Serialize + for<'de> Deserialize<'de> + PartialEq + std::fmt::Debug,
+    {
+        let encoded = to_bytes(&value).expect("serialization failed");
+        assert_eq!(from_bytes::<T>(&encoded), Ok(value));
+    }
+
+    proptest! {
+        #[test]
+        fn struct_roundtrip(
+            pubkey: Vec<u8>,
+            nonce: u64,
+            balance: u128,
+            flags: Option<u32>,
+            name: String,
+        ) {
+            roundtrip(Account { pubkey, nonce, balance, flags, name });
+        }
+    }
+
+    #[test]
+    fn matches_native_encoding() {
+        let account = Account {
+            pubkey: vec![1; 32],
+            nonce: 0,
+            balance: 1_000_000_000_000_000_000,
+            flags: None,
+            name: "alice".to_string(),
+        };
+        let expect = (
+            vec![1u8; 32],
+            0u64,
+            1_000_000_000_000_000_000u128,
+            None::<u32>,
+            b"alice".to_vec(),
+        );
+        assert_eq!(to_item(&account), Ok(expect.to_rlp_item()));
+    }
+
+    #[test]
+    fn enums() {
+        assert_eq!(to_item(&Event::Ping), Ok(RlpItem::ByteArray(vec![0])));
+        assert_eq!(to_item(&Event::Height(7)), Ok((1u8, 7u8).to_rlp_item()));
+        roundtrip(Event::Ping);
+        roundtrip(Event::Height(1000));
+        roundtrip(Event::Move(1, 2));
+        roundtrip(Event::Rename {
+            from: "a".to_string(),
+            to: "b".to_string(),
+        });
+        roundtrip(vec![Event::Ping, Event::Height(1)]);
+    }
+
+    #[test]
+    fn collections() {
+        roundtrip(BTreeMap::from([
+            (1u32, "one".to_string()),
+            (2, "two".to_string()),
+        ]));
+        roundtrip((1u8, -5i64 + 10, 'x', ()));
+        roundtrip(Some(vec![Some(1u8), None]));
+    }
+
+    #[test]
+    fn borrows_input() {
+        let encoded = to_bytes(&Borrowed {
+            payload: &[1, 2, 3],
+            label: "label",
+        })
+        .unwrap();
+        let decoded: Borrowed = from_bytes(&encoded).unwrap();
+        assert_eq!(decoded.payload, &[1, 2, 3]);
+        assert_eq!(decoded.label, "label");
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            to_bytes(&-1i32),
+            Err(SerdeErr::Unsupported("negative integer"))
+        );
+        assert_eq!(
+            to_bytes(&1.0f64),
+            Err(SerdeErr::Unsupported("floating point number"))
+        );
+
+        let trailing = to_bytes(&(1u8, 2u8, 3u8)).unwrap();
+        assert_eq!(
+            from_bytes::<(u8, u8)>(&trailing),
+            Err(SerdeErr::TrailingElements)
+        );
+
+        let leading_zero = RlpItem::ByteArray(vec![0, 1]).serialize();
+        assert_eq!(
+            from_bytes::<u64>(&leading_zero),
+            Err(SerdeErr::Decoding(error::DecodingErr::InvalidInt))
+        );
+
+        assert_eq!(
+            from_bytes::<u64>(&[]),
+            Err(SerdeErr::Rlp(DecodingErr::Empty))
+        );
+
+        let too_short = to_bytes(&(serde_bytes::Bytes::new(&[1]), 1u8)).unwrap();
+        assert!(matches!(
+            from_bytes::<Account>(&too_short),
+            Err(SerdeErr::Message(_))
+        ));
+    }
+
+    #[test]
+    fn ignored_fields() {
+        let encoded = to_bytes(&(1u8, (2u8, "x"), 3u8)).unwrap();
+        assert_eq!(
+            from_bytes::<(u8, de::IgnoredAny, u8)>(&encoded),
+            Ok((1, de::IgnoredAny, 3))
+        );
+
+        #[derive(Serialize)]
+        struct Sparse {
+            #[serde(skip_serializing_if = "Option::is_none")]
+            flags: Option<u32>,
+            nonce: u64,
+        }
+        assert_eq!(
+            to_bytes(&Sparse { flags: None, nonce: 1 }),
+            Err(SerdeErr::Unsupported("skipped struct field"))
+        );
+        assert!(to_bytes(&Sparse { flags: Some(1), nonce: 1 }).is_ok());
+    }
+}
@@ -0,0 +1,411 @@
+//! Textual form of RLP items. `{}` formats an item on a single line, such as
+//! `[0x2a, [0x01, 0x]]`, where byte arrays are written in hex. `{:#}` lays out one item per line
+//! and annotates each with a `#` comment holding its offset and size in the serialized encoding,
+//! along with integer and ASCII interpretations of byte arrays.
+//!
+//! Both forms can be parsed back with [str::parse]. The parser additionally accepts byte arrays
+//! written as `"strings"` (with `\\`, `\"`, `\n`, `\t` and `\xNN` escapes) and as decimal
+//! integers, which are encoded as minimal big-endian byte arrays.
+
+use std::fmt::{self, Write};
+use std::str::FromStr;
+
+use super::*;
+
+/// Longest byte array which also gets an integer interpretation in the pretty form.
+const MAX_PRETTY_INT_SIZE: usize = 16;
+
+impl fmt::Display for RlpItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            let mut lines = Vec::new();
+            pretty_lines(self, 0, 0, "", &mut lines);
+            f.write_str(&lines.join("\n"))
+        } else {
+            write_compact(f, self)
+        }
+    }
+}
+
+fn write_compact(f: &mut fmt::Formatter<'_>, item: &RlpItem) -> fmt::Result {
+    match item {
+        RlpItem::ByteArray(bytes) => write_hex(f, bytes),
+        RlpItem::List(items) => {
+            f.write_char('[')?;
+            for (i, x) in items.iter().enumerate() {
+                if i > 0 {
+                    f.write_str(", ")?;
+                }
+                write_compact(f, x)?;
+            }
+            f.write_char(']')
+        }
+    }
+}
+
+fn write_hex(out: &mut impl Write, bytes: &[u8]) -> fmt::Result {
+    out.write_str("0x")?;
+    bytes.iter().try_for_each(|b| write!(out, "{b:02x}"))
+}
+
+/// Appends lines describing `item`, located at `offset` of the serialized encoding and nested
+/// `depth` lists deep. `sep` is written right after the item.
+fn pretty_lines(item: &RlpItem, offset: usize, depth: usize, sep: &str, lines: &mut Vec<String>) {
+    let indent = "  ".repeat(depth);
+    let header_len = item.encoded_len() - payload_len(item);
+
+    match item {
+        RlpItem::ByteArray(bytes) => {
+            let mut line = indent;
+            // Writing to a String never fails.
+            let _ = write_hex(&mut line, bytes);
+            line.push_str(sep);
+            let _ = write!(line, "  # @{offset}: {} bytes", bytes.len());
+            if let Some(n) = interpret_int(bytes) {
+                let _ = write!(line, ", int {n}");
+            }
+            if let Some(s) = interpret_ascii(bytes) {
+                let _ = write!(line, ", ascii {s}");
+            }
+            lines.push(line);
+        }
+        RlpItem::List(items) if items.is_empty() => {
+            lines.push(format!("{indent}[]{sep}  # @{offset}: empty list"));
+        }
+        RlpItem::List(items) => {
+            lines.push(format!(
+                "{indent}[  # @{offset}: list of {} items, {} bytes",
+                items.len(),
+                payload_len(item)
+            ));
+            let mut at = offset + header_len;
+            for (i, x) in items.iter().enumerate() {
+                let sep = if i + 1 < items.len() { "," } else { "" };
+                pretty_lines(x, at, depth + 1, sep, lines);
+                at += x.encoded_len();
+            }
+            lines.push(format!("{indent}]{sep}"));
+        }
+    }
+}
+
+fn payload_len(item: &RlpItem) -> usize {
+    match item {
+        RlpItem::ByteArray(bytes) if bytes.len() == 1 && bytes[0] <= UNTAGGED_LIMIT => 1,
+        RlpItem::ByteArray(bytes) => bytes.len(),
+        RlpItem::List(items) => items.iter().map(|x| x.encoded_len()).sum(),
+    }
+}
+
+fn interpret_int(bytes: &[u8]) -> Option<u128> {
+    if bytes.is_empty() || bytes.len() > MAX_PRETTY_INT_SIZE || (bytes.len() > 1 && bytes[0] == 0) {
+        return None;
+    }
+    Some(bytes.iter().fold(0, |acc, b| (acc << 8) | *b as u128))
+}
+
+fn interpret_ascii(bytes: &[u8]) -> Option<String> {
+    if bytes.is_empty() || !bytes.iter().all(|b| (0x20..=0x7e).contains(b)) {
+        return None;
+    }
+
+    let mut s = String::with_capacity(bytes.len() + 2);
+    s.push('"');
+    for b in bytes {
+        if *b == b'"' || *b == b'\\' {
+            s.push('\\');
+        }
+        s.push(*b as char);
+    }
+    s.push('"');
+    Some(s)
+}
+
+/// An error while parsing the textual form of an RLP item. Positions are byte offsets in the
+/// text.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseErr {
+    /// Text ends in the middle of an item.
+    UnexpectedEnd,
+    /// Character which cannot appear at the given position.
+    UnexpectedChar { position: usize, found: char },
+    /// Odd number of hex digits, or a non-hex character in a hex byte array.
+    InvalidHex { position: usize },
+    /// Unknown escape sequence in a string.
+    InvalidEscape { position: usize },
+    /// More text follows the item.
+    Trailing { position: usize },
+}
+
+impl fmt::Display for ParseErr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseErr::UnexpectedEnd => f.write_str("unexpected end of text"),
+            ParseErr::UnexpectedChar { position, found } => {
+                write!(f, "unexpected {found:?} at position {position}")
+            }
+            ParseErr::InvalidHex { position } => {
+                write!(f, "malformed hex byte array at position {position}")
+            }
+            ParseErr::InvalidEscape { position } => {
+                write!(f, "invalid escape sequence at position {position}")
+            }
+            ParseErr::Trailing { position } => {
+                write!(f, "unexpected text after the item at position {position}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseErr {}
+
+impl FromStr for RlpItem {
+    type Err = ParseErr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser { text: s, pos: 0 };
+        let item = parser.item()?;
+        parser.skip_blank();
+        if parser.pos < s.len() {
+            Err(ParseErr::Trailing {
+                position: parser.pos,
+            })?
+        }
+        Ok(item)
+    }
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<char> {
+        self.text[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn unexpected(&self) -> ParseErr {
+        match self.peek() {
+            Some(found) => ParseErr::UnexpectedChar {
+                position: self.pos,
+                found,
+            },
+            None => ParseErr::UnexpectedEnd,
+        }
+    }
+
+    /// Skips whitespace and comments.
+    fn skip_blank(&mut self) {
+        while let Some(c) = self.peek() {
+            if c == '#' {
+                while !matches!(self.bump(), Some('\n') | None) {}
+            } else if c.is_whitespace() {
+                self.bump();
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn item(&mut self) -> Result<RlpItem, ParseErr> {
+        self.skip_blank();
+        match self.peek() {
+            Some('[') => self.list(),
+            Some('"') => self.string(),
+            Some('0') if self.text[self.pos..].starts_with("0x") => self.hex(),
+            Some(c) if c.is_ascii_digit() => Ok(self.int()),
+            _ => Err(self.unexpected()),
+        }
+    }
+
+    fn list(&mut self) -> Result<RlpItem, ParseErr> {
+        self.bump();
+        let mut items = Vec::new();
+        loop {
+            self.skip_blank();
+            if self.peek() == Some(']') {
+                self.bump();
+                return Ok(RlpItem::List(items));
+            }
+
+            items.push(self.item()?);
+
+            self.skip_blank();
+            match self.peek() {
+                Some(',') => {
+                    self.bump();
+                }
+                Some(']') => (),
+                _ => Err(self.unexpected())?,
+            }
+        }
+    }
+
+    fn hex(&mut self) -> Result<RlpItem, ParseErr> {
+        let start = self.pos;
+        self.pos += 2;
+        let digits = self.take_while(|c| c.is_ascii_alphanumeric());
+
+        if !digits.len().is_multiple_of(2) {
+            Err(ParseErr::InvalidHex { position: start })?
+        }
+
+        (0..digits.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&digits[i..i + 2], 16))
+            .collect::<Result<_, _>>()
+            .map(RlpItem::ByteArray)
+            .map_err(|_| ParseErr::InvalidHex { position: start })
+    }
+
+    fn int(&mut self) -> RlpItem {
+        let digits = self.take_while(|c| c.is_ascii_digit());
+        // Only digits were taken, so parsing cannot fail.
+        num::BigUint::parse_bytes(digits.as_bytes(), 10)
+            .unwrap_or_default()
+            .to_rlp_item()
+    }
+
+    fn string(&mut self) -> Result<RlpItem, ParseErr> {
+        self.bump();
+        let mut bytes = Vec::new();
+        loop {
+            let escape_at = self.pos;
+            match self.bump().ok_or(ParseErr::UnexpectedEnd)? {
+                '"' => return Ok(RlpItem::ByteArray(bytes)),
+                '\\' => {
+                    let invalid = ParseErr::InvalidEscape {
+                        position: escape_at,
+                    };
+                    match self.bump().ok_or(ParseErr::UnexpectedEnd)? {
+                        '\\' => bytes.push(b'\\'),
+                        '"' => bytes.push(b'"'),
+                        'n' => bytes.push(b'\n'),
+                        't' => bytes.push(b'\t'),
+                        'x' => {
+                            let digits = self
+                                .text
+                                .get(self.pos..self.pos + 2)
+                                .filter(|d| d.bytes().all(|c| c.is_ascii_hexdigit()))
+                                .ok_or(invalid.clone())?;
+                            let b = u8::from_str_radix(digits, 16).map_err(|_| invalid)?;
+                            self.pos += 2;
+                            bytes.push(b);
+                        }
+                        _ => Err(invalid)?,
+                    }
+                }
+                c => bytes.extend(c.encode_utf8(&mut [0; 4]).as_bytes()),
+            }
+        }
+    }
+
+    fn take_while(&mut self, pred: impl Fn(char) -> bool) -> &'a str {
+        let start = self.pos;
+        while self.peek().is_some_and(&pred) {
+            self.bump();
+        }
+        &self.text[start..self.pos]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn sample() -> RlpItem {
+        RlpItem::List(vec![
+            RlpItem::ByteArray(vec![42]),
+            RlpItem::List(vec![]),
+            RlpItem::List(vec![RlpItem::ByteArray(b"ab\"c".to_vec())]),
+            RlpItem::ByteArray(vec![]),
+        ])
+    }
+
+    proptest! {
+        #[test]
+        fn display_parse_roundtrip(item: RlpItem) {
+            prop_assert_eq!(format!("{item}").parse::<RlpItem>(), Ok(item.clone()));
+            prop_assert_eq!(format!("{item:#}").parse::<RlpItem>(), Ok(item));
+        }
+    }
+
+    #[test]
+    fn compact() {
+        assert_eq!(sample().to_string(), "[0x2a, [], [0x61622263], 0x]");
+    }
+
+    #[test]
+    fn pretty() {
+        let expect = "\
+[  # @0: list of 4 items, 9 bytes
+  0x2a,  # @1: 1 bytes, int 42, ascii \"*\"
+  [],  # @2: empty list
+  [  # @3: list of 1 items, 5 bytes
+    0x61622263  # @4: 4 bytes, int 1633821283, ascii \"ab\\\"c\"
+  ],
+  0x  # @9: 0 bytes
+]";
+        assert_eq!(format!("{:#}", sample()), expect);
+    }
+
+    #[test]
+    fn parse_fixture() {
+        let text = r#"
+            # Objects can be written with comments and readable fields.
+            [
+                42,           # tag
+                [],
+                ["ab\"c"],
+                "",           # trailing comma is fine,
+            ]
+        "#;
+        assert_eq!(text.parse(), Ok(sample()));
+        assert_eq!(
+            "[0, 255, 256]".parse(),
+            Ok((0u8, 255u8, 256u16).to_rlp_item())
+        );
+        assert_eq!("\"\\x00\\n\"".parse(), Ok(RlpItem::ByteArray(vec![0, 10])));
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!("".parse::<RlpItem>(), Err(ParseErr::UnexpectedEnd));
+        assert_eq!("[0x01".parse::<RlpItem>(), Err(ParseErr::UnexpectedEnd));
+        assert_eq!(
+            "[1 2]".parse::<RlpItem>(),
+            Err(ParseErr::UnexpectedChar {
+                position: 3,
+                found: '2'
+            })
+        );
+        assert_eq!(
+            "[0x123]".parse::<RlpItem>(),
+            Err(ParseErr::InvalidHex { position: 1 })
+        );
+        assert_eq!(
+            "0xzz".parse::<RlpItem>(),
+            Err(ParseErr::InvalidHex { position: 0 })
+        );
+        assert_eq!(
+            "\"\\q\"".parse::<RlpItem>(),
+            Err(ParseErr::InvalidEscape { position: 1 })
+        );
+        assert_eq!(
+            "\"\\x+1\"".parse::<RlpItem>(),
+            Err(ParseErr::InvalidEscape { position: 1 })
+        );
+        assert_eq!(
+            "[] []".parse::<RlpItem>(),
+            Err(ParseErr::Trailing { position: 3 })
+        );
+    }
+}
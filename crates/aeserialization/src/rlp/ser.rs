@@ -0,0 +1,395 @@
+//! Serde serializer producing RLP. Structs, tuples and sequences become positional lists, unsigned
+//! integers become minimal big-endian byte arrays, and strings and byte buffers (see
+//! `serde_bytes`) become byte arrays. Enum variants are identified by their index: unit variants
+//! are encoded as the bare index, while other variants as a list of the index followed by the
+//! fields. Maps become lists of key-value pairs. [None] is an empty byte array, so [Some] must not
+//! hold a value encoded as one. Struct fields skipped conditionally (`skip_serializing_if`) are
+//! rejected, as they would shift the position of the following fields.
+
+use std::fmt;
+
+use serde::ser::{self, Serialize};
+
+use super::*;
+
+/// An error while serializing or deserializing a serde data structure.
+#[derive(Debug, PartialEq)]
+pub enum SerdeErr {
+    /// Error reported by the serialized or deserialized type.
+    Message(String),
+    /// Malformed RLP input.
+    Rlp(DecodingErr),
+    /// Well-formed RLP which does not match the expected type.
+    Decoding(error::DecodingErr),
+    /// List holds more elements than the expected type.
+    TrailingElements,
+    /// Value has no RLP representation (eg. floats or negative integers).
+    Unsupported(&'static str),
+}
+
+impl fmt::Display for SerdeErr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SerdeErr::Message(msg) => f.write_str(msg),
+            SerdeErr::Rlp(_) => f.write_str("malformed RLP"),
+            SerdeErr::Decoding(_) => f.write_str("RLP item does not match the expected type"),
+            SerdeErr::TrailingElements => f.write_str("list holds too many elements"),
+            SerdeErr::Unsupported(what) => write!(f, "{what} cannot be encoded in RLP"),
+        }
+    }
+}
+
+impl std::error::Error for SerdeErr {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SerdeErr::Rlp(e) => Some(e),
+            SerdeErr::Decoding(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<DecodingErr> for SerdeErr {
+    fn from(e: DecodingErr) -> Self {
+        SerdeErr::Rlp(e)
+    }
+}
+
+impl From<error::DecodingErr> for SerdeErr {
+    fn from(e: error::DecodingErr) -> Self {
+        SerdeErr::Decoding(e)
+    }
+}
+
+impl ser::Error for SerdeErr {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        SerdeErr::Message(msg.to_string())
+    }
+}
+
+/// Converts a serde data structure into an [RlpItem].
+pub fn to_item<T: Serialize + ?Sized>(value: &T) -> Result<RlpItem, SerdeErr> {
+    value.serialize(Serializer)
+}
+
+/// Serializes a serde data structure into RLP.
+pub fn to_bytes<T: Serialize + ?Sized>(value: &T) -> Result<Bytes, SerdeErr> {
+    to_item(value).map(|x| x.serialize())
+}
+
+/// Serde serializer producing an [RlpItem].
+pub struct Serializer;
+
+/// Collects elements of any serde compound type into an RLP list.
+pub struct ListSerializer {
+    items: Vec<RlpItem>,
+    key: Option<RlpItem>,
+}
+
+impl ListSerializer {
+    fn new(len: Option<usize>, variant: Option<u32>) -> Self {
+        let mut items = Vec::with_capacity(len.unwrap_or(0) + 1);
+        items.extend(variant.map(|x| x.to_rlp_item()));
+        ListSerializer { items, key: None }
+    }
+
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeErr> {
+        self.items.push(to_item(value)?);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<RlpItem, SerdeErr> {
+        Ok(RlpItem::List(self.items))
+    }
+}
+
+impl ser::Serializer for Serializer {
+    type Ok = RlpItem;
+    type Error = SerdeErr;
+
+    type SerializeSeq = ListSerializer;
+    type SerializeTuple = ListSerializer;
+    type SerializeTupleStruct = ListSerializer;
+    type SerializeTupleVariant = ListSerializer;
+    type SerializeMap = ListSerializer;
+    type SerializeStruct = ListSerializer;
+    type SerializeStructVariant = ListSerializer;
+
+    fn serialize_bool(self, v: bool) -> Result<RlpItem, SerdeErr> {
+        Ok(v.to_rlp_item())
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<RlpItem, SerdeErr> {
+        self.serialize_i128(v as i128)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<RlpItem, SerdeErr> {
+        self.serialize_i128(v as i128)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<RlpItem, SerdeErr> {
+        self.serialize_i128(v as i128)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<RlpItem, SerdeErr> {
+        self.serialize_i128(v as i128)
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<RlpItem, SerdeErr> {
+        u128::try_from(v)
+            .map(|x| x.to_rlp_item())
+            .map_err(|_| SerdeErr::Unsupported("negative integer"))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<RlpItem, SerdeErr> {
+        Ok(v.to_rlp_item())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<RlpItem, SerdeErr> {
+        Ok(v.to_rlp_item())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<RlpItem, SerdeErr> {
+        Ok(v.to_rlp_item())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<RlpItem, SerdeErr> {
+        Ok(v.to_rlp_item())
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<RlpItem, SerdeErr> {
+        Ok(v.to_rlp_item())
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<RlpItem, SerdeErr> {
+        Err(SerdeErr::Unsupported("floating point number"))
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<RlpItem, SerdeErr> {
+        Err(SerdeErr::Unsupported("floating point number"))
+    }
+
+    fn serialize_char(self, v: char) -> Result<RlpItem, SerdeErr> {
+        self.serialize_str(v.encode_utf8(&mut [0; 4]))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<RlpItem, SerdeErr> {
+        Ok(RlpItem::ByteArray(v.as_bytes().to_vec()))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<RlpItem, SerdeErr> {
+        Ok(RlpItem::ByteArray(v.to_vec()))
+    }
+
+    fn serialize_none(self) -> Result<RlpItem, SerdeErr> {
+        Ok(RlpItem::ByteArray(vec![]))
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<RlpItem, SerdeErr> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<RlpItem, SerdeErr> {
+        Ok(RlpItem::List(vec![]))
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<RlpItem, SerdeErr> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<RlpItem, SerdeErr> {
+        Ok(variant_index.to_rlp_item())
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<RlpItem, SerdeErr> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        value: &T,
+    ) -> Result<RlpItem, SerdeErr> {
+        Ok(RlpItem::List(vec![
+            variant_index.to_rlp_item(),
+            value.serialize(self)?,
+        ]))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<ListSerializer, SerdeErr> {
+        Ok(ListSerializer::new(len, None))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<ListSerializer, SerdeErr> {
+        Ok(ListSerializer::new(Some(len), None))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<ListSerializer, SerdeErr> {
+        Ok(ListSerializer::new(Some(len), None))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        len: usize,
+    ) -> Result<ListSerializer, SerdeErr> {
+        Ok(ListSerializer::new(Some(len), Some(variant_index)))
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<ListSerializer, SerdeErr> {
+        Ok(ListSerializer::new(len, None))
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<ListSerializer, SerdeErr> {
+        Ok(ListSerializer::new(Some(len), None))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        len: usize,
+    ) -> Result<ListSerializer, SerdeErr> {
+        Ok(ListSerializer::new(Some(len), Some(variant_index)))
+    }
+}
+
+impl ser::SerializeSeq for ListSerializer {
+    type Ok = RlpItem;
+    type Error = SerdeErr;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeErr> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<RlpItem, SerdeErr> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTuple for ListSerializer {
+    type Ok = RlpItem;
+    type Error = SerdeErr;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeErr> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<RlpItem, SerdeErr> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleStruct for ListSerializer {
+    type Ok = RlpItem;
+    type Error = SerdeErr;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeErr> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<RlpItem, SerdeErr> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleVariant for ListSerializer {
+    type Ok = RlpItem;
+    type Error = SerdeErr;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeErr> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<RlpItem, SerdeErr> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeMap for ListSerializer {
+    type Ok = RlpItem;
+    type Error = SerdeErr;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), SerdeErr> {
+        self.key = Some(to_item(key)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeErr> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| SerdeErr::Message("map value without a key".to_string()))?;
+        self.items.push(RlpItem::List(vec![key, to_item(value)?]));
+        Ok(())
+    }
+
+    fn end(self) -> Result<RlpItem, SerdeErr> {
+        self.finish()
+    }
+}
+
+/// Fields are identified by their position, so a skipped field would shift the following ones.
+fn skipped_field() -> Result<(), SerdeErr> {
+    Err(SerdeErr::Unsupported("skipped struct field"))
+}
+
+impl ser::SerializeStruct for ListSerializer {
+    type Ok = RlpItem;
+    type Error = SerdeErr;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<(), SerdeErr> {
+        self.push(value)
+    }
+
+    fn skip_field(&mut self, _key: &'static str) -> Result<(), SerdeErr> {
+        skipped_field()
+    }
+
+    fn end(self) -> Result<RlpItem, SerdeErr> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStructVariant for ListSerializer {
+    type Ok = RlpItem;
+    type Error = SerdeErr;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<(), SerdeErr> {
+        self.push(value)
+    }
+
+    fn skip_field(&mut self, _key: &'static str) -> Result<(), SerdeErr> {
+        skipped_field()
+    }
+
+    fn end(self) -> Result<RlpItem, SerdeErr> {
+        self.finish()
+    }
+}
@@ -0,0 +1,416 @@
+use std::io::{self, Read, Write};
+
+use super::*;
+
+/// An error while streaming RLP items.
+#[derive(Debug)]
+pub enum StreamErr {
+    /// Failure of the underlying reader or writer.
+    Io(io::Error),
+    /// Malformed item in the stream. Positions are absolute offsets in the stream.
+    Decoding(DecodingErr),
+}
+
+impl std::fmt::Display for StreamErr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StreamErr::Io(_) => f.write_str("I/O error while streaming RLP"),
+            StreamErr::Decoding(_) => f.write_str("malformed RLP in stream"),
+        }
+    }
+}
+
+impl std::error::Error for StreamErr {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            StreamErr::Io(e) => Some(e),
+            StreamErr::Decoding(e) => Some(e),
+        }
+    }
+}
+
+impl From<io::Error> for StreamErr {
+    fn from(e: io::Error) -> Self {
+        StreamErr::Io(e)
+    }
+}
+
+impl From<DecodingErr> for StreamErr {
+    fn from(e: DecodingErr) -> Self {
+        StreamErr::Decoding(e)
+    }
+}
+
+/// Reads consecutive RLP items from a byte stream, such as a concatenation of serialized blocks.
+/// Only a single top-level item is kept in memory at a time.
+///
+/// The size description of each item is read with separate small reads, so unbuffered sources
+/// such as files or sockets should be wrapped in a [std::io::BufReader]. After an error, the
+/// position in the stream is lost, and no further items are read.
+pub struct RlpReader<R> {
+    inner: R,
+    position: usize,
+    buf: Bytes,
+    limits: DecodingLimits,
+    failed: bool,
+}
+
+impl<R: Read> RlpReader<R> {
+    pub fn new(inner: R) -> Self {
+        Self::with_limits(inner, DecodingLimits::default())
+    }
+
+    /// Creates a reader which rejects items exceeding given limits. Oversized items are rejected
+    /// based on their size description, before their payload is read.
+    pub fn with_limits(inner: R, limits: DecodingLimits) -> Self {
+        RlpReader {
+            inner,
+            position: 0,
+            buf: Vec::new(),
+            limits,
+            failed: false,
+        }
+    }
+
+    /// Absolute offset of the next item in the stream.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Unwraps the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Reads the next item. Returns [None] if the stream ends at an item boundary.
+    pub fn read_item(&mut self) -> Result<Option<RlpItem>, StreamErr> {
+        match self.read_ref()? {
+            Some(item) => Ok(Some(item.to_item()?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Reads the next item as a view into the internal buffer. The view is valid until the next
+    /// read. Returns [None] if the stream ends at an item boundary.
+    pub fn read_ref(&mut self) -> Result<Option<RlpRef<'_>>, StreamErr> {
+        let start = self.position;
+        let max_depth = self.limits.max_depth;
+        match self.read_raw()? {
+            Some(bytes) => {
+                let (item, _) = RlpRef::try_decode_at(bytes, start, max_depth)?;
+                Ok(Some(item))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Reads the next item without decoding it. Returns its encoding, including the size
+    /// description. Returns [None] if the stream ends at an item boundary, or if a previous read
+    /// failed.
+    pub fn read_raw(&mut self) -> Result<Option<&[u8]>, StreamErr> {
+        if self.failed {
+            return Ok(None);
+        }
+        match self.fill_next() {
+            Ok(true) => Ok(Some(&self.buf)),
+            Ok(false) => Ok(None),
+            Err(e) => {
+                self.failed = true;
+                Err(e)
+            }
+        }
+    }
+
+    /// Reads the next item into the buffer. Returns whether there was one.
+    fn fill_next(&mut self) -> Result<bool, StreamErr> {
+        self.buf.clear();
+
+        let mut first = [0; 1];
+        loop {
+            match self.inner.read(&mut first) {
+                Ok(0) => return Ok(false),
+                Ok(_) => break,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => Err(e)?,
+            }
+        }
+        self.buf.push(first[0]);
+
+        let header_len = Header::size_of(first[0]);
+        if header_len > 1 {
+            self.fill(header_len - 1, header_len)?;
+        }
+        let header = Header::decode(&self.buf, self.position)?;
+
+        if header.total_len() > self.limits.max_size {
+            Err(DecodingErr::SizeLimitExceeded {
+                position: self.position,
+                limit: self.limits.max_size,
+                actual: header.total_len(),
+            })?
+        }
+
+        // Single bytes below 128 are their own header.
+        let missing = header.total_len() - self.buf.len();
+        self.fill(missing, header.total_len())?;
+        self.position += self.buf.len();
+
+        Ok(true)
+    }
+
+    /// Appends exactly `n` bytes from the stream to the buffer. `expected` is the total size of
+    /// the item being read, reported if the stream ends prematurely.
+    fn fill(&mut self, n: usize, expected: usize) -> Result<(), StreamErr> {
+        let start = self.buf.len();
+        (&mut self.inner)
+            .take(n as u64)
+            .read_to_end(&mut self.buf)?;
+
+        if self.buf.len() - start < n {
+            Err(DecodingErr::SizeOverflow {
+                position: self.position,
+                expected,
+                actual: self.buf.len(),
+            })?
+        }
+
+        Ok(())
+    }
+}
+
+impl<R: Read> std::iter::FusedIterator for RlpReader<R> {}
+
+impl<R: Read> Iterator for RlpReader<R> {
+    type Item = Result<RlpItem, StreamErr>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let next = self.read_item().transpose();
+        self.failed = matches!(next, Some(Err(_)));
+        next
+    }
+}
+
+/// Writes RLP items to a byte stream. Nested items are written directly, without serializing
+/// list payloads into intermediate buffers. Lists can also be written element by element after
+/// announcing their payload size with [RlpWriter::begin_list].
+pub struct RlpWriter<W> {
+    inner: W,
+    position: usize,
+}
+
+impl<W: Write> RlpWriter<W> {
+    pub fn new(inner: W) -> Self {
+        RlpWriter { inner, position: 0 }
+    }
+
+    /// Number of bytes written so far.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Unwraps the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
+    /// Writes a byte array.
+    pub fn write_bytes(&mut self, bytes: &[u8]) -> io::Result<()> {
+        if bytes.len() == 1 && bytes[0] <= UNTAGGED_LIMIT {
+            self.write_raw(bytes)
+        } else {
+            self.write_header(BYTE_ARRAY_OFFSET, bytes.len())?;
+            self.write_raw(bytes)
+        }
+    }
+
+    /// Starts a list with a payload of `payload_len` bytes. The elements have to be written
+    /// afterwards, and their encodings have to add up to exactly `payload_len` bytes.
+    pub fn begin_list(&mut self, payload_len: usize) -> io::Result<()> {
+        self.write_header(LIST_OFFSET, payload_len)
+    }
+
+    /// Writes an item along with all of its nested items.
+    pub fn write_item(&mut self, item: &RlpItem) -> io::Result<()> {
+        let mut list_lens = Vec::new();
+        item.collect_list_lens(&mut list_lens);
+        self.write_nested(item, &list_lens, &mut 0)
+    }
+
+    /// Writes a value convertible to an RLP item.
+    pub fn write<T: ToRlpItem + ?Sized>(&mut self, value: &T) -> io::Result<()> {
+        self.write_item(&value.to_rlp_item())
+    }
+
+    /// Flushes the underlying writer.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+
+    /// Writes an item using list payload sizes from [RlpItem::collect_list_lens], starting at
+    /// index `next`.
+    fn write_nested(
+        &mut self,
+        item: &RlpItem,
+        list_lens: &[usize],
+        next: &mut usize,
+    ) -> io::Result<()> {
+        match item {
+            RlpItem::ByteArray(bytes) => self.write_bytes(bytes),
+            RlpItem::List(items) => {
+                self.begin_list(list_lens[*next])?;
+                *next += 1;
+                items
+                    .iter()
+                    .try_for_each(|x| self.write_nested(x, list_lens, next))
+            }
+        }
+    }
+
+    fn write_header(&mut self, offset: u8, len: usize) -> io::Result<()> {
+        let (header, header_len) = encode_header(offset, len);
+        self.write_raw(&header[..header_len])
+    }
+
+    fn write_raw(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.inner.write_all(bytes)?;
+        self.position += bytes.len();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn write_read_roundtrip(items in prop::collection::vec(any::<RlpItem>(), 0..5)) {
+            let mut writer = RlpWriter::new(Vec::new());
+            for item in &items {
+                writer.write_item(item).expect("writing failed");
+            }
+            let encoded = writer.into_inner();
+            let expect: Bytes = items.iter().flat_map(|x| x.serialize()).collect();
+            prop_assert_eq!(&encoded, &expect);
+
+            let reader = RlpReader::new(&encoded[..]);
+            let decoded = reader.collect::<Result<Vec<_>, _>>().expect("reading failed");
+            prop_assert_eq!(decoded, items);
+        }
+    }
+
+    #[test]
+    fn positions() {
+        let first = RlpItem::ByteArray(vec![200; 100]);
+        let second = RlpItem::List(vec![RlpItem::ByteArray(vec![1]), RlpItem::List(vec![])]);
+        let input = [first.serialize(), second.serialize()].concat();
+
+        let mut reader = RlpReader::new(&input[..]);
+        assert_eq!(reader.read_item().unwrap(), Some(first));
+        assert_eq!(reader.position(), 102);
+        assert_eq!(reader.read_item().unwrap(), Some(second));
+        assert_eq!(reader.position(), 105);
+        assert_eq!(reader.read_item().unwrap(), None);
+    }
+
+    #[test]
+    fn truncated_stream() {
+        let item = RlpItem::ByteArray(vec![200; 100]).serialize();
+        let input = [&item[..], &item[..50]].concat();
+
+        let mut reader = RlpReader::new(&input[..]);
+        assert!(reader.read_item().unwrap().is_some());
+        match reader.read_item() {
+            Err(StreamErr::Decoding(e)) => assert_eq!(
+                e,
+                DecodingErr::SizeOverflow {
+                    position: 102,
+                    expected: 102,
+                    actual: 50
+                }
+            ),
+            res => panic!("Unexpected result: {res:?}"),
+        }
+    }
+
+    #[test]
+    fn fused_after_error() {
+        // The second item declares 100 bytes of payload, while only 3 follow. They would be read
+        // as items if reading went on.
+        let input = [0x01, 0xb8, 0x64, 0x01, 0x02, 0x03];
+
+        let mut reader = RlpReader::new(&input[..]);
+        assert!(matches!(reader.next(), Some(Ok(_))));
+        assert!(matches!(reader.next(), Some(Err(StreamErr::Decoding(_)))));
+        assert!(reader.next().is_none());
+        assert!(matches!(reader.read_raw(), Ok(None)));
+    }
+
+    #[test]
+    fn nested_positions() {
+        // The inner list declares two bytes of payload, while it only holds one.
+        let input = [0x01, 0xc2, 0xc2, 0x05];
+
+        let mut reader = RlpReader::new(&input[..]);
+        assert!(reader.read_item().unwrap().is_some());
+        match reader.read_item() {
+            Err(StreamErr::Decoding(e)) => assert_eq!(
+                e,
+                DecodingErr::SizeOverflow {
+                    position: 2,
+                    expected: 3,
+                    actual: 2
+                }
+            ),
+            res => panic!("Unexpected result: {res:?}"),
+        }
+    }
+
+    #[test]
+    fn size_limit_before_payload() {
+        let item = RlpItem::ByteArray(vec![200; 100]).serialize();
+        let limits = DecodingLimits {
+            max_size: 101,
+            ..Default::default()
+        };
+
+        // Only the size description is available, so any attempt to read the payload would
+        // result in a different error.
+        let mut reader = RlpReader::with_limits(&item[..2], limits);
+        match reader.read_item() {
+            Err(StreamErr::Decoding(e)) => assert_eq!(
+                e,
+                DecodingErr::SizeLimitExceeded {
+                    position: 0,
+                    limit: 101,
+                    actual: 102
+                }
+            ),
+            res => panic!("Unexpected result: {res:?}"),
+        }
+    }
+
+    #[test]
+    fn incremental_list() {
+        let elems = [
+            RlpItem::ByteArray(vec![7; 40]),
+            RlpItem::ByteArray(vec![8; 40]),
+        ];
+        let expect = RlpItem::List(elems.to_vec()).serialize();
+
+        let mut writer = RlpWriter::new(Vec::new());
+        writer
+            .begin_list(elems.iter().map(|x| x.serialize().len()).sum())
+            .unwrap();
+        for elem in &elems {
+            writer.write_bytes(&elem.byte_array().unwrap()).unwrap();
+        }
+
+        assert_eq!(writer.position(), expect.len());
+        assert_eq!(writer.into_inner(), expect);
+    }
+}
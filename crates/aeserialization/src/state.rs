@@ -0,0 +1,312 @@
+//! Objects stored in the state trees, such as accounts and contracts, as found in proofs of
+//! inclusion and state tree dumps. Objects whose layout changed across protocols have a struct per
+//! version, and are decoded into an enum of their versions.
+
+macro_rules! versioned {
+    ($(#[$attr:meta])* $name:ident, $object:literal { $($variant:ident($ty:ident) = $vsn:literal,)* }) => {
+        $(#[$attr])*
+        #[derive(Debug, Clone, PartialEq)]
+        pub enum $name {
+            $($variant($ty),)*
+        }
+
+        impl $name {
+            /// Serialization version of the object.
+            pub fn version(&self) -> u8 {
+                match self {
+                    $($name::$variant(_) => $vsn,)*
+                }
+            }
+        }
+
+        impl $crate::rlp::ToRlpItem for $name {
+            fn to_rlp_item(&self) -> $crate::rlp::RlpItem {
+                match self {
+                    $($name::$variant(x) => x.to_rlp_item(),)*
+                }
+            }
+        }
+
+        impl $crate::rlp::FromRlpItem for $name {
+            fn from_rlp_item(
+                item: &$crate::rlp::RlpItem,
+            ) -> Result<Self, $crate::error::DecodingErr> {
+                let invalid = || $crate::error::DecodingErr::InvalidVersion { object: $object };
+                let vsn = item.as_list()?.get(1).ok_or_else(invalid)?;
+                match u8::from_rlp_item(vsn) {
+                    $(Ok($vsn) => $ty::from_rlp_item(item).map($name::$variant),)*
+                    _ => Err(invalid()),
+                }
+            }
+        }
+
+        impl<'a> $crate::rlp::FromRlpRef<'a> for $name {
+            fn from_rlp_ref(
+                item: &$crate::rlp::RlpRef<'a>,
+            ) -> Result<Self, $crate::error::DecodingErr> {
+                let invalid = || $crate::error::DecodingErr::InvalidVersion { object: $object };
+                let vsn = item.list()?.nth(1).transpose()?.ok_or_else(invalid)?;
+                match u8::from_rlp_ref(&vsn) {
+                    $(Ok($vsn) => $ty::from_rlp_ref(item).map($name::$variant),)*
+                    _ => Err(invalid()),
+                }
+            }
+        }
+
+        $(
+            impl From<$ty> for $name {
+                fn from(x: $ty) -> Self {
+                    $name::$variant(x)
+                }
+            }
+        )*
+    };
+}
+
+pub mod account;
+pub mod channel;
+pub mod contract;
+pub mod name;
+pub mod oracle;
+
+pub use account::*;
+pub use channel::*;
+pub use contract::*;
+pub use name::*;
+pub use oracle::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::DecodingErr;
+    use crate::id::tests::account;
+    use crate::id::{Id, Tag};
+    use crate::mpt::{Mpt, Poi, StateTree};
+    use crate::rlp::{FromRlpItem, FromRlpRef, RlpItem, ToRlpItem};
+    use crate::tx::NamePointer;
+    use proptest::prelude::*;
+
+    fn roundtrip<T>(x: T)
+    where
+        T: ToRlpItem + FromRlpItem + for<'a> FromRlpRef<'a> + PartialEq + std::fmt::Debug,
+    {
+        let bytes = x.serialize_rlp();
+        assert_eq!(T::deserialize_rlp(&bytes).as_ref(), Ok(&x));
+        assert_eq!(T::deserialize_rlp_ref(&bytes), Ok(x));
+    }
+
+    proptest! {
+        #[test]
+        fn account_roundtrip(nonce: u64, balance: u128, flags: u64) {
+            let v1 = Account::from(AccountV1 { nonce, balance });
+            let v2 = Account::from(AccountV2 {
+                flags,
+                nonce,
+                balance,
+                ga_contract: Id::new(Tag::Contract, [1; 32]),
+                ga_auth_fun: vec![2; 32],
+            });
+            for account in [v1, v2] {
+                prop_assert_eq!(account.nonce(), nonce);
+                prop_assert_eq!(account.balance(), balance);
+                roundtrip(account);
+            }
+        }
+    }
+
+    #[test]
+    fn account_layout() {
+        let account = Account::from(AccountV1 {
+            nonce: 3,
+            balance: 1000,
+        });
+        let expect = RlpItem::List(vec![
+            RlpItem::ByteArray(vec![10]),
+            RlpItem::ByteArray(vec![1]),
+            RlpItem::ByteArray(vec![3]),
+            RlpItem::ByteArray(vec![0x03, 0xe8]),
+        ]);
+        assert_eq!(account.to_rlp_item(), expect);
+        assert_eq!(account.version(), 1);
+    }
+
+    #[test]
+    fn unknown_versions() {
+        let item = RlpItem::List(vec![
+            RlpItem::ByteArray(vec![10]),
+            RlpItem::ByteArray(vec![9]),
+            RlpItem::ByteArray(vec![3]),
+        ]);
+        let err = || DecodingErr::InvalidVersion { object: "Account" };
+        assert_eq!(Account::from_rlp_item(&item), Err(err()));
+        assert_eq!(Account::deserialize_rlp_ref(&item.serialize()), Err(err()));
+        assert_eq!(Account::from_rlp_item(&RlpItem::List(vec![])), Err(err()));
+
+        // The tag is still checked by the version.
+        let item = RlpItem::List(vec![
+            RlpItem::ByteArray(vec![20]),
+            RlpItem::ByteArray(vec![1]),
+            RlpItem::ByteArray(vec![3]),
+            RlpItem::ByteArray(vec![4]),
+        ]);
+        assert_eq!(
+            Account::from_rlp_item(&item),
+            Err(DecodingErr::InvalidTag {
+                object: "AccountV1"
+            })
+        );
+    }
+
+    #[test]
+    fn oracles() {
+        roundtrip(Oracle {
+            owner_id: account(1),
+            query_format: b"string".to_vec(),
+            response_format: b"int".to_vec(),
+            query_fee: 10,
+            expires: 500,
+            abi_version: 1,
+        });
+    }
+
+    #[test]
+    fn contracts() {
+        let v1 = ContractV1 {
+            owner_id: account(1),
+            ct_version: 0x70003,
+            code: vec![1; 40],
+            log: vec![],
+            active: true,
+            referrer_ids: vec![Id::new(Tag::Contract, [2; 32])],
+            deposit: 0,
+        };
+        let contract = Contract::from(v1.clone());
+        assert_eq!(contract.vm_version(), 7);
+        assert_eq!(contract.abi_version(), 3);
+        assert_eq!(contract.code(), ContractCode::Code(&v1.code));
+        roundtrip(contract);
+
+        let reference = Id::new(Tag::Contract, [3; 32]);
+        let contract = Contract::from(ContractV2 {
+            owner_id: v1.owner_id,
+            ct_version: v1.ct_version,
+            code: reference,
+            log: v1.log,
+            active: false,
+            referrer_ids: vec![],
+            deposit: 5,
+        });
+        assert_eq!(contract.code(), ContractCode::Reference(&reference));
+        assert_eq!(contract.version(), 2);
+        roundtrip(contract);
+    }
+
+    #[test]
+    fn oracle_queries() {
+        roundtrip(OracleQuery {
+            sender_id: account(1),
+            sender_nonce: 7,
+            oracle_id: Id::new(Tag::Oracle, [2; 32]),
+            query: b"how are you?".to_vec(),
+            has_response: true,
+            response: b"fine".to_vec(),
+            expires: 1000,
+            response_ttl: 10,
+            fee: 100,
+        });
+    }
+
+    #[test]
+    fn names() {
+        roundtrip(Name {
+            owner_id: account(1),
+            expires_by: 180_000,
+            status: b"claimed".to_vec(),
+            client_ttl: 3600,
+            pointers: vec![NamePointer {
+                key: b"account_pubkey".to_vec(),
+                id: account(1),
+            }],
+        });
+        roundtrip(NameCommitment {
+            owner_id: account(1),
+            created: 10,
+            expires: 310,
+        });
+    }
+
+    #[test]
+    fn channels() {
+        let v1 = ChannelV1 {
+            initiator_id: account(1),
+            responder_id: account(2),
+            channel_amount: 100,
+            initiator_amount: 60,
+            responder_amount: 40,
+            channel_reserve: 5,
+            delegate_ids: vec![account(3)],
+            state_hash: vec![4; 32],
+            round: 2,
+            solo_round: 0,
+            lock_period: 10,
+            locked_until: 0,
+        };
+        let v2 = ChannelV2 {
+            initiator_id: v1.initiator_id,
+            responder_id: v1.responder_id,
+            channel_amount: v1.channel_amount,
+            initiator_amount: v1.initiator_amount,
+            responder_amount: v1.responder_amount,
+            channel_reserve: v1.channel_reserve,
+            delegate_ids: v1.delegate_ids.clone(),
+            state_hash: v1.state_hash.clone(),
+            round: v1.round,
+            solo_round: v1.solo_round,
+            lock_period: v1.lock_period,
+            locked_until: v1.locked_until,
+            initiator_auth: account(1),
+            responder_auth: Id::new(Tag::Contract, [5; 32]),
+        };
+        let v3 = ChannelV3 {
+            initiator_id: v1.initiator_id,
+            responder_id: v1.responder_id,
+            channel_amount: v1.channel_amount,
+            initiator_amount: v1.initiator_amount,
+            responder_amount: v1.responder_amount,
+            channel_reserve: v1.channel_reserve,
+            initiator_delegate_ids: vec![],
+            responder_delegate_ids: vec![account(3)],
+            state_hash: v1.state_hash.clone(),
+            round: 3,
+            solo_round: 1,
+            lock_period: v1.lock_period,
+            locked_until: 20,
+            initiator_auth: account(1),
+            responder_auth: account(2),
+        };
+        for (channel, vsn) in [(v1.into(), 1), (v2.into(), 2), (v3.into(), 3)] {
+            let channel: Channel = channel;
+            assert_eq!(channel.version(), vsn);
+            assert_eq!(channel.initiator_id(), &account(1));
+            assert_eq!(channel.state_hash(), &[4; 32]);
+            roundtrip(channel);
+        }
+    }
+
+    #[test]
+    fn proven_account() {
+        let mut accounts = Mpt::new();
+        for n in 1..10 {
+            let account = Account::from(AccountV1 {
+                nonce: n.into(),
+                balance: 1000,
+            });
+            accounts.insert(&[n; 32], account.serialize_rlp()).unwrap();
+        }
+        let mut poi = Poi::new();
+        poi.add(StateTree::Accounts, &accounts, &[4; 32]).unwrap();
+
+        let leaf = poi.lookup(StateTree::Accounts, &[4; 32]).unwrap().unwrap();
+        assert_eq!(Account::deserialize_rlp_ref(leaf).unwrap().nonce(), 4);
+    }
+}
@@ -0,0 +1,52 @@
+use crate::id::Id;
+use crate::rlp::{FromRlpItem, FromRlpRef, ToRlpItem};
+use crate::Bytes;
+
+/// Identifier tag of an account.
+pub const ACCOUNT_TAG: u16 = 10;
+
+/// Basic account.
+#[derive(Debug, Clone, PartialEq, ToRlpItem, FromRlpItem, FromRlpRef)]
+#[rlp(tag = ACCOUNT_TAG, vsn = 1u8)]
+pub struct AccountV1 {
+    pub nonce: u64,
+    pub balance: u128,
+}
+
+/// Account supporting generalized authentication, introduced in the Fortuna protocol.
+#[derive(Debug, Clone, PartialEq, ToRlpItem, FromRlpItem, FromRlpRef)]
+#[rlp(tag = ACCOUNT_TAG, vsn = 2u8)]
+pub struct AccountV2 {
+    pub flags: u64,
+    /// Zero for generalized accounts, which do not use nonces.
+    pub nonce: u64,
+    pub balance: u128,
+    /// Contract authenticating the transactions of a generalized account.
+    pub ga_contract: Id,
+    /// Hash of the authentication function.
+    pub ga_auth_fun: Bytes,
+}
+
+versioned! {
+    /// Account of any version.
+    Account, "Account" {
+        V1(AccountV1) = 1,
+        V2(AccountV2) = 2,
+    }
+}
+
+impl Account {
+    pub fn nonce(&self) -> u64 {
+        match self {
+            Account::V1(a) => a.nonce,
+            Account::V2(a) => a.nonce,
+        }
+    }
+
+    pub fn balance(&self) -> u128 {
+        match self {
+            Account::V1(a) => a.balance,
+            Account::V2(a) => a.balance,
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use crate::id::Id;
+use crate::rlp::{FromRlpItem, FromRlpRef, ToRlpItem};
+use crate::Bytes;
+
+/// Identifier tag of a state channel.
+pub const CHANNEL_TAG: u16 = 58;
+
+/// State channel of the Roma protocol.
+#[derive(Debug, Clone, PartialEq, ToRlpItem, FromRlpItem, FromRlpRef)]
+#[rlp(tag = CHANNEL_TAG, vsn = 1u8)]
+pub struct ChannelV1 {
+    pub initiator_id: Id,
+    pub responder_id: Id,
+    pub channel_amount: u128,
+    pub initiator_amount: u128,
+    pub responder_amount: u128,
+    pub channel_reserve: u128,
+    pub delegate_ids: Vec<Id>,
+    pub state_hash: Bytes,
+    pub round: u64,
+    pub solo_round: u64,
+    pub lock_period: u64,
+    pub locked_until: u64,
+}
+
+/// State channel supporting generalized accounts as participants.
+#[derive(Debug, Clone, PartialEq, ToRlpItem, FromRlpItem, FromRlpRef)]
+#[rlp(tag = CHANNEL_TAG, vsn = 2u8)]
+pub struct ChannelV2 {
+    pub initiator_id: Id,
+    pub responder_id: Id,
+    pub channel_amount: u128,
+    pub initiator_amount: u128,
+    pub responder_amount: u128,
+    pub channel_reserve: u128,
+    pub delegate_ids: Vec<Id>,
+    pub state_hash: Bytes,
+    pub round: u64,
+    pub solo_round: u64,
+    pub lock_period: u64,
+    pub locked_until: u64,
+    /// Authentication of the initiator, either its account or a contract of a generalized
+    /// account.
+    pub initiator_auth: Id,
+    pub responder_auth: Id,
+}
+
+/// State channel with delegates of each participant kept apart.
+#[derive(Debug, Clone, PartialEq, ToRlpItem, FromRlpItem, FromRlpRef)]
+#[rlp(tag = CHANNEL_TAG, vsn = 3u8)]
+pub struct ChannelV3 {
+    pub initiator_id: Id,
+    pub responder_id: Id,
+    pub channel_amount: u128,
+    pub initiator_amount: u128,
+    pub responder_amount: u128,
+    pub channel_reserve: u128,
+    pub initiator_delegate_ids: Vec<Id>,
+    pub responder_delegate_ids: Vec<Id>,
+    pub state_hash: Bytes,
+    pub round: u64,
+    pub solo_round: u64,
+    pub lock_period: u64,
+    pub locked_until: u64,
+    pub initiator_auth: Id,
+    pub responder_auth: Id,
+}
+
+versioned! {
+    /// State channel of any version.
+    Channel, "Channel" {
+        V1(ChannelV1) = 1,
+        V2(ChannelV2) = 2,
+        V3(ChannelV3) = 3,
+    }
+}
+
+impl Channel {
+    pub fn initiator_id(&self) -> &Id {
+        match self {
+            Channel::V1(c) => &c.initiator_id,
+            Channel::V2(c) => &c.initiator_id,
+            Channel::V3(c) => &c.initiator_id,
+        }
+    }
+
+    pub fn responder_id(&self) -> &Id {
+        match self {
+            Channel::V1(c) => &c.responder_id,
+            Channel::V2(c) => &c.responder_id,
+            Channel::V3(c) => &c.responder_id,
+        }
+    }
+
+    pub fn state_hash(&self) -> &[u8] {
+        match self {
+            Channel::V1(c) => &c.state_hash,
+            Channel::V2(c) => &c.state_hash,
+            Channel::V3(c) => &c.state_hash,
+        }
+    }
+
+    pub fn round(&self) -> u64 {
+        match self {
+            Channel::V1(c) => c.round,
+            Channel::V2(c) => c.round,
+            Channel::V3(c) => c.round,
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use crate::id::Id;
+use crate::rlp::{FromRlpItem, FromRlpRef, ToRlpItem};
+use crate::Bytes;
+
+/// Identifier tag of a contract.
+pub const CONTRACT_TAG: u16 = 40;
+
+/// Contract holding its own code. Its store is kept in the contracts tree under separate keys.
+#[derive(Debug, Clone, PartialEq, ToRlpItem, FromRlpItem, FromRlpRef)]
+#[rlp(tag = CONTRACT_TAG, vsn = 1u8)]
+pub struct ContractV1 {
+    pub owner_id: Id,
+    /// VM version in the upper 16 bits and ABI version in the lower 16 bits.
+    pub ct_version: u32,
+    /// Serialized [crate::contract_code::Code].
+    pub code: Bytes,
+    pub log: Bytes,
+    pub active: bool,
+    pub referrer_ids: Vec<Id>,
+    pub deposit: u128,
+}
+
+/// Contract reusing the code of another contract, such as a clone.
+#[derive(Debug, Clone, PartialEq, ToRlpItem, FromRlpItem, FromRlpRef)]
+#[rlp(tag = CONTRACT_TAG, vsn = 2u8)]
+pub struct ContractV2 {
+    pub owner_id: Id,
+    /// VM version in the upper 16 bits and ABI version in the lower 16 bits.
+    pub ct_version: u32,
+    /// Contract holding the code.
+    pub code: Id,
+    pub log: Bytes,
+    pub active: bool,
+    pub referrer_ids: Vec<Id>,
+    pub deposit: u128,
+}
+
+versioned! {
+    /// Contract of any version.
+    Contract, "Contract" {
+        V1(ContractV1) = 1,
+        V2(ContractV2) = 2,
+    }
+}
+
+/// Code of a contract, either held by the contract or referenced.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ContractCode<'a> {
+    Code(&'a [u8]),
+    Reference(&'a Id),
+}
+
+impl Contract {
+    pub fn owner_id(&self) -> &Id {
+        match self {
+            Contract::V1(c) => &c.owner_id,
+            Contract::V2(c) => &c.owner_id,
+        }
+    }
+
+    pub fn ct_version(&self) -> u32 {
+        match self {
+            Contract::V1(c) => c.ct_version,
+            Contract::V2(c) => c.ct_version,
+        }
+    }
+
+    pub fn vm_version(&self) -> u16 {
+        (self.ct_version() >> 16) as u16
+    }
+
+    pub fn abi_version(&self) -> u16 {
+        self.ct_version() as u16
+    }
+
+    pub fn code(&self) -> ContractCode<'_> {
+        match self {
+            Contract::V1(c) => ContractCode::Code(&c.code),
+            Contract::V2(c) => ContractCode::Reference(&c.code),
+        }
+    }
+
+    pub fn active(&self) -> bool {
+        match self {
+            Contract::V1(c) => c.active,
+            Contract::V2(c) => c.active,
+        }
+    }
+
+    pub fn deposit(&self) -> u128 {
+        match self {
+            Contract::V1(c) => c.deposit,
+            Contract::V2(c) => c.deposit,
+        }
+    }
+}
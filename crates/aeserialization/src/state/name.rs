@@ -0,0 +1,33 @@
+use crate::id::Id;
+use crate::rlp::{FromRlpItem, FromRlpRef, ToRlpItem};
+use crate::tx::NamePointer;
+use crate::Bytes;
+
+/// Identifier tag of a name.
+pub const NAME_TAG: u16 = 30;
+/// Identifier tag of a name commitment.
+pub const NAME_COMMITMENT_TAG: u16 = 31;
+
+/// Claimed name.
+#[derive(Debug, Clone, PartialEq, ToRlpItem, FromRlpItem, FromRlpRef)]
+#[rlp(tag = NAME_TAG, vsn = 1u8)]
+pub struct Name {
+    pub owner_id: Id,
+    /// Height at which the name expires.
+    pub expires_by: u64,
+    /// Either `claimed` or `revoked`.
+    pub status: Bytes,
+    pub client_ttl: u64,
+    pub pointers: Vec<NamePointer>,
+}
+
+/// Preclaim commitment to a name. See [crate::id::commitment_id].
+#[derive(Debug, Clone, PartialEq, ToRlpItem, FromRlpItem, FromRlpRef)]
+#[rlp(tag = NAME_COMMITMENT_TAG, vsn = 1u8)]
+pub struct NameCommitment {
+    pub owner_id: Id,
+    /// Height of the preclaim.
+    pub created: u64,
+    /// Height at which the commitment expires.
+    pub expires: u64,
+}
@@ -0,0 +1,39 @@
+use crate::id::Id;
+use crate::rlp::{FromRlpItem, FromRlpRef, ToRlpItem};
+use crate::Bytes;
+
+/// Identifier tag of an oracle.
+pub const ORACLE_TAG: u16 = 20;
+/// Identifier tag of an oracle query.
+pub const ORACLE_QUERY_TAG: u16 = 21;
+
+/// Registered oracle.
+#[derive(Debug, Clone, PartialEq, ToRlpItem, FromRlpItem, FromRlpRef)]
+#[rlp(tag = ORACLE_TAG, vsn = 1u8)]
+pub struct Oracle {
+    pub owner_id: Id,
+    pub query_format: Bytes,
+    pub response_format: Bytes,
+    pub query_fee: u128,
+    /// Height at which the oracle expires.
+    pub expires: u64,
+    pub abi_version: u16,
+}
+
+/// Query posted to an oracle, along with its response once given.
+#[derive(Debug, Clone, PartialEq, ToRlpItem, FromRlpItem, FromRlpRef)]
+#[rlp(tag = ORACLE_QUERY_TAG, vsn = 1u8)]
+pub struct OracleQuery {
+    pub sender_id: Id,
+    pub sender_nonce: u64,
+    pub oracle_id: Id,
+    pub query: Bytes,
+    pub has_response: bool,
+    /// Empty until the oracle responds.
+    pub response: Bytes,
+    /// Height at which the query expires.
+    pub expires: u64,
+    /// Number of blocks the response is kept for.
+    pub response_ttl: u64,
+    pub fee: u128,
+}
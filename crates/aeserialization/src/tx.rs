@@ -0,0 +1,573 @@
+//! Transactions and their signing. A transaction is signed by prepending the network id to the
+//! hash of its serialization, and signatures are carried along with the serialized transaction in
+//! a [SignedTx]. Typed models of the protocol transactions are grouped by subsystem, and any of
+//! them can be decoded into a [Tx] based on its object tag.
+
+use crate::api_encoder::{AccountPubkey, EncodedTx, Signature, TxHash};
+use crate::error::DecodingErr;
+use crate::keys::{self, KeyErr, KeyPair};
+use crate::rlp::{FromRlpItem, FromRlpRef, RlpItem, RlpRef, ToRlpItem};
+use crate::{hash, Bytes};
+
+pub mod channel;
+pub mod contract;
+pub mod ga;
+pub mod name;
+pub mod oracle;
+pub mod spend;
+
+pub use channel::*;
+pub use contract::*;
+pub use ga::*;
+pub use name::*;
+pub use oracle::*;
+pub use spend::*;
+
+/// Identifier tag of a signed transaction.
+pub const SIGNED_TX_TAG: u8 = 11;
+
+/// Signed transaction format version.
+pub const SIGNED_TX_VSN: u8 = 1;
+
+/// Describes which data is signed along with the network id.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum SigningScheme {
+    /// Hash of the serialized transaction. Used since the Lima protocol.
+    #[default]
+    TxHash,
+    /// Serialized transaction. Used before the Lima protocol.
+    TxBytes,
+}
+
+/// Returns the data signed in a transaction for the given network, eg. `ae_mainnet`.
+pub fn data_to_sign(network_id: &str, tx: &[u8], scheme: SigningScheme) -> Bytes {
+    match scheme {
+        SigningScheme::TxHash => [network_id.as_bytes(), &hash(tx)].concat(),
+        SigningScheme::TxBytes => [network_id.as_bytes(), tx].concat(),
+    }
+}
+
+/// Transaction along with its signatures. Fields are laid out in the order of serialization.
+#[derive(Debug, Clone, PartialEq, ToRlpItem, FromRlpItem, FromRlpRef)]
+#[rlp(tag = SIGNED_TX_TAG, vsn = SIGNED_TX_VSN)]
+pub struct SignedTx {
+    /// Signatures of the transaction. The node keeps them sorted and without duplicates, as does
+    /// [SignedTx::sign].
+    pub signatures: Vec<Bytes>,
+    /// Serialized transaction.
+    pub transaction: Bytes,
+}
+
+impl SignedTx {
+    /// Wraps a serialized transaction without any signatures.
+    pub fn new(transaction: Bytes) -> Self {
+        SignedTx {
+            signatures: Vec::new(),
+            transaction,
+        }
+    }
+
+    /// Adds a signature of the transaction for the given network, using the current
+    /// [SigningScheme].
+    pub fn sign(&mut self, keypair: &KeyPair, network_id: &str) {
+        self.sign_with_scheme(keypair, network_id, SigningScheme::TxHash)
+    }
+
+    /// Adds a signature of the transaction for the given network. Signatures are kept sorted, and
+    /// a signature that is already present is not added again.
+    pub fn sign_with_scheme(&mut self, keypair: &KeyPair, network_id: &str, scheme: SigningScheme) {
+        let data = data_to_sign(network_id, &self.transaction, scheme);
+        let signature = keypair.sign(&data).into_bytes();
+        if let Err(pos) = self.signatures.binary_search(&signature) {
+            self.signatures.insert(pos, signature);
+        }
+    }
+
+    /// Verifies that each signature was made by a distinct signer and that each signer made one of
+    /// the signatures.
+    pub fn verify(
+        &self,
+        network_id: &str,
+        signers: &[AccountPubkey],
+        scheme: SigningScheme,
+    ) -> Result<(), KeyErr> {
+        if signers.len() != self.signatures.len() {
+            Err(KeyErr::InvalidSignature)?;
+        }
+
+        let data = data_to_sign(network_id, &self.transaction, scheme);
+        let mut unmatched: Vec<&Bytes> = self.signatures.iter().collect();
+        for signer in signers {
+            let pos = unmatched
+                .iter()
+                .position(|sig| {
+                    Signature::new(sig.to_vec())
+                        .is_ok_and(|sig| keys::verify(signer, &data, &sig).is_ok())
+                })
+                .ok_or(KeyErr::InvalidSignature)?;
+            unmatched.swap_remove(pos);
+        }
+        Ok(())
+    }
+
+    /// Decodes a signed transaction from its `tx_` encoding.
+    pub fn from_encoded(tx: &EncodedTx) -> Result<Self, DecodingErr> {
+        Self::deserialize_rlp_ref(tx.as_bytes())
+    }
+
+    /// Encodes the signed transaction as `tx_`.
+    pub fn to_encoded(&self) -> EncodedTx {
+        EncodedTx::new(self.serialize_rlp()).expect("transactions are not size constrained")
+    }
+
+    /// Hash of the signed transaction, under which it is tracked by the node. Changes whenever a
+    /// signature is added.
+    pub fn hash(&self) -> TxHash {
+        tx_hash(&self.serialize_rlp())
+    }
+}
+
+fn tx_hash(signed_tx: &[u8]) -> TxHash {
+    TxHash::new(hash(signed_tx).to_vec()).expect("hash of an invalid size")
+}
+
+/// Computes the `th_` hash of a `tx_` encoded [SignedTx]. Fails if the string does not hold a signed
+/// transaction.
+pub fn hash_encoded(tx: &str) -> Result<TxHash, DecodingErr> {
+    let tx: EncodedTx = tx.parse()?;
+    SignedTx::from_encoded(&tx)?;
+    Ok(tx_hash(tx.as_bytes()))
+}
+
+macro_rules! transactions {
+    ($($variant:ident($ty:ident) = $tag:path;)*) => {
+        /// Any unsigned transaction. Decoding dispatches on the object tag.
+        #[derive(Debug, Clone, PartialEq)]
+        pub enum Tx {
+            $($variant($ty),)*
+        }
+
+        impl Tx {
+            /// Object tag of the transaction.
+            pub fn tag(&self) -> u16 {
+                match self {
+                    $(Tx::$variant(_) => $tag,)*
+                }
+            }
+        }
+
+        impl ToRlpItem for Tx {
+            fn to_rlp_item(&self) -> RlpItem {
+                match self {
+                    $(Tx::$variant(tx) => tx.to_rlp_item(),)*
+                }
+            }
+        }
+
+        impl FromRlpItem for Tx {
+            fn from_rlp_item(item: &RlpItem) -> Result<Self, DecodingErr> {
+                let tag = item.as_list()?.first().ok_or(INVALID_TAG)?;
+                match u16::from_rlp_item(tag).map_err(|_| INVALID_TAG)? {
+                    $($tag => $ty::from_rlp_item(item).map(Tx::$variant),)*
+                    _ => Err(INVALID_TAG),
+                }
+            }
+        }
+
+        impl<'a> FromRlpRef<'a> for Tx {
+            fn from_rlp_ref(item: &RlpRef<'a>) -> Result<Self, DecodingErr> {
+                let tag = item.list()?.next().transpose()?.ok_or(INVALID_TAG)?;
+                match u16::from_rlp_ref(&tag).map_err(|_| INVALID_TAG)? {
+                    $($tag => $ty::from_rlp_ref(item).map(Tx::$variant),)*
+                    _ => Err(INVALID_TAG),
+                }
+            }
+        }
+
+        $(
+            impl From<$ty> for Tx {
+                fn from(tx: $ty) -> Self {
+                    Tx::$variant(tx)
+                }
+            }
+        )*
+    };
+}
+
+const INVALID_TAG: DecodingErr = DecodingErr::InvalidTag {
+    object: "transaction",
+};
+
+transactions! {
+    Spend(SpendTx) = SPEND_TX_TAG;
+    OracleRegister(OracleRegisterTx) = ORACLE_REGISTER_TX_TAG;
+    OracleQuery(OracleQueryTx) = ORACLE_QUERY_TX_TAG;
+    OracleResponse(OracleResponseTx) = ORACLE_RESPONSE_TX_TAG;
+    OracleExtend(OracleExtendTx) = ORACLE_EXTEND_TX_TAG;
+    NameClaim(NameClaimTx) = NAME_CLAIM_TX_TAG;
+    NamePreclaim(NamePreclaimTx) = NAME_PRECLAIM_TX_TAG;
+    NameUpdate(NameUpdateTx) = NAME_UPDATE_TX_TAG;
+    NameRevoke(NameRevokeTx) = NAME_REVOKE_TX_TAG;
+    NameTransfer(NameTransferTx) = NAME_TRANSFER_TX_TAG;
+    ContractCreate(ContractCreateTx) = CONTRACT_CREATE_TX_TAG;
+    ContractCall(ContractCallTx) = CONTRACT_CALL_TX_TAG;
+    ChannelCreate(ChannelCreateTx) = CHANNEL_CREATE_TX_TAG;
+    ChannelDeposit(ChannelDepositTx) = CHANNEL_DEPOSIT_TX_TAG;
+    ChannelWithdraw(ChannelWithdrawTx) = CHANNEL_WITHDRAW_TX_TAG;
+    ChannelCloseMutual(ChannelCloseMutualTx) = CHANNEL_CLOSE_MUTUAL_TX_TAG;
+    ChannelCloseSolo(ChannelCloseSoloTx) = CHANNEL_CLOSE_SOLO_TX_TAG;
+    ChannelSlash(ChannelSlashTx) = CHANNEL_SLASH_TX_TAG;
+    ChannelSettle(ChannelSettleTx) = CHANNEL_SETTLE_TX_TAG;
+    ChannelOffchain(ChannelOffchainTx) = CHANNEL_OFFCHAIN_TX_TAG;
+    ChannelSnapshotSolo(ChannelSnapshotSoloTx) = CHANNEL_SNAPSHOT_SOLO_TX_TAG;
+    ChannelSetDelegates(ChannelSetDelegatesTx) = CHANNEL_SET_DELEGATES_TX_TAG;
+    ChannelForceProgress(ChannelForceProgressTx) = CHANNEL_FORCE_PROGRESS_TX_TAG;
+    GaAttach(GaAttachTx) = GA_ATTACH_TX_TAG;
+    GaMeta(GaMetaTx) = GA_META_TX_TAG;
+    PayingFor(PayingForTx) = PAYING_FOR_TX_TAG;
+}
+
+impl Tx {
+    /// Decodes a transaction from its `tx_` encoding.
+    pub fn from_encoded(tx: &EncodedTx) -> Result<Self, DecodingErr> {
+        Self::deserialize_rlp_ref(tx.as_bytes())
+    }
+
+    /// Encodes the transaction as `tx_`.
+    pub fn to_encoded(&self) -> EncodedTx {
+        EncodedTx::new(self.serialize_rlp()).expect("transactions are not size constrained")
+    }
+}
+
+/// Signs a `tx_` encoded transaction for the given network. Returns the `tx_` encoded [SignedTx].
+pub fn sign_encoded(tx: &EncodedTx, network_id: &str, keypair: &KeyPair) -> EncodedTx {
+    let mut signed = SignedTx::new(tx.as_bytes().to_vec());
+    signed.sign(keypair, network_id);
+    signed.to_encoded()
+}
+
+/// Signs a transaction for the given network. Returns the `tx_` encoded [SignedTx].
+pub fn sign<T: ToRlpItem + ?Sized>(tx: &T, network_id: &str, keypair: &KeyPair) -> EncodedTx {
+    let mut signed = SignedTx::new(tx.serialize_rlp());
+    signed.sign(keypair, network_id);
+    signed.to_encoded()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::id::tests::account;
+    use crate::id::{Id, Tag};
+    use proptest::prelude::*;
+
+    const NETWORK_ID: &str = "ae_uat";
+
+    proptest! {
+        #[test]
+        fn signed_tx_roundtrip(
+            tx in prop::collection::vec(any::<u8>(), 0..64),
+            seeds in prop::collection::btree_set(any::<[u8; 32]>(), 1..4),
+        ) {
+            let keypairs: Vec<_> = seeds.iter().map(KeyPair::from_seed).collect();
+            let mut signed = SignedTx::new(tx);
+            for kp in &keypairs {
+                signed.sign(kp, NETWORK_ID);
+            }
+
+            prop_assert!(signed.signatures.windows(2).all(|w| w[0] < w[1]));
+
+            let decoded = SignedTx::from_encoded(&signed.to_encoded()).expect("decoding failed");
+            prop_assert_eq!(&decoded, &signed);
+
+            let mut signers: Vec<_> = keypairs.iter().map(|kp| kp.public_key()).collect();
+            signers.reverse();
+            prop_assert_eq!(decoded.verify(NETWORK_ID, &signers, SigningScheme::TxHash), Ok(()));
+            prop_assert!(decoded.verify("ae_mainnet", &signers, SigningScheme::TxHash).is_err());
+            prop_assert!(decoded.verify(NETWORK_ID, &signers, SigningScheme::TxBytes).is_err());
+        }
+    }
+
+    #[test]
+    fn layout() {
+        let signed = SignedTx {
+            signatures: vec![vec![1; 64]],
+            transaction: vec![2; 10],
+        };
+        let expect = RlpItem::List(vec![
+            RlpItem::ByteArray(vec![SIGNED_TX_TAG]),
+            RlpItem::ByteArray(vec![SIGNED_TX_VSN]),
+            RlpItem::List(vec![RlpItem::ByteArray(vec![1; 64])]),
+            RlpItem::ByteArray(vec![2; 10]),
+        ]);
+        assert_eq!(signed.to_rlp_item(), expect);
+    }
+
+    #[test]
+    fn signing_encoded() {
+        let kp = KeyPair::from_seed(&[1; 32]);
+        let tx = EncodedTx::new(vec![3; 20]).unwrap();
+        let signed = SignedTx::from_encoded(&sign_encoded(&tx, NETWORK_ID, &kp)).unwrap();
+        assert_eq!(signed.transaction, tx.as_bytes());
+
+        let data = data_to_sign(NETWORK_ID, tx.as_bytes(), SigningScheme::TxHash);
+        assert_eq!(&data[..NETWORK_ID.len()], NETWORK_ID.as_bytes());
+        assert_eq!(signed.signatures, vec![kp.sign(&data).into_bytes()]);
+    }
+
+    fn sample_txs() -> Vec<Tx> {
+        let spend = SpendTx {
+            sender_id: account(1),
+            recipient_id: Id::new(Tag::Name, [2; 32]),
+            amount: 10u128.pow(18),
+            fee: 16_660_000_000_000,
+            ttl: 0,
+            nonce: 1,
+            payload: b"hello".to_vec(),
+        };
+        let claim = NameClaimTx {
+            account_id: account(1),
+            nonce: 2,
+            name: b"foo.chain".to_vec(),
+            name_salt: num::BigUint::from(u128::MAX) * 7u32,
+            name_fee: 0,
+            fee: 1,
+            ttl: 100,
+        };
+        let update = NameUpdateTx {
+            account_id: account(1),
+            nonce: 3,
+            name_id: Id::new(Tag::Name, [3; 32]),
+            name_ttl: 180_000,
+            pointers: vec![NamePointer {
+                key: b"account_pubkey".to_vec(),
+                id: account(1),
+            }],
+            client_ttl: 3600,
+            fee: 1,
+            ttl: 0,
+        };
+        let create = ChannelCreateTx {
+            initiator_id: account(1),
+            initiator_amount: 50,
+            responder_id: account(2),
+            responder_amount: 60,
+            channel_reserve: 5,
+            lock_period: 10,
+            ttl: 0,
+            fee: 1,
+            initiator_delegate_ids: vec![account(3)],
+            responder_delegate_ids: vec![],
+            state_hash: vec![4; 32],
+            nonce: 1,
+        };
+        let delegates = ChannelSetDelegatesTx {
+            channel_id: Id::new(Tag::Channel, [5; 32]),
+            from_id: account(1),
+            initiator_delegate_ids: vec![],
+            responder_delegate_ids: vec![account(4), account(5)],
+            state_hash: vec![6; 32],
+            round: 7,
+            payload: vec![],
+            ttl: 0,
+            fee: 1,
+            nonce: 2,
+        };
+        let meta = GaMetaTx {
+            ga_id: account(1),
+            auth_data: vec![7; 10],
+            abi_version: 3,
+            fee: 1,
+            gas: 50_000,
+            gas_price: 1_000_000_000,
+            tx: SignedTx::new(spend.serialize_rlp()).serialize_rlp(),
+        };
+        vec![
+            spend.into(),
+            claim.into(),
+            update.into(),
+            create.into(),
+            delegates.into(),
+            meta.into(),
+        ]
+    }
+
+    #[test]
+    fn tx_roundtrip() {
+        for tx in sample_txs() {
+            let encoded = tx.to_encoded();
+            assert_eq!(Tx::from_encoded(&encoded), Ok(tx.clone()));
+            assert_eq!(Tx::deserialize_rlp(encoded.as_bytes()), Ok(tx.clone()));
+
+            let tag: u16 = RlpItem::deserialize(encoded.as_bytes()).unwrap().get(0).unwrap();
+            assert_eq!(tag, tx.tag());
+        }
+    }
+
+    #[test]
+    fn tags_and_versions() {
+        // Tag, version and number of fields including both, from the serialization templates of
+        // `aec_spend_tx`, `aens_claim_tx`, `aens_update_tx`, `aesc_create_tx`,
+        // `aesc_set_delegates_tx` and `aega_meta_tx`.
+        let expect = [(12, 1, 9), (32, 2, 9), (34, 1, 10), (50, 2, 14), (501, 1, 12), (81, 2, 9)];
+        for (tx, (tag, vsn, len)) in sample_txs().iter().zip(expect) {
+            let item = tx.to_rlp_item();
+            assert_eq!(item.get::<u16>(0), Ok(tag));
+            assert_eq!(item.get::<u8>(1), Ok(vsn));
+            assert!(matches!(item, RlpItem::List(ref fields) if fields.len() == len));
+        }
+    }
+
+    #[test]
+    fn spend_layout() {
+        let tx = SpendTx {
+            sender_id: account(1),
+            recipient_id: account(2),
+            amount: 256,
+            fee: 20,
+            ttl: 0,
+            nonce: 1,
+            payload: vec![],
+        };
+        let expect = RlpItem::List(vec![
+            RlpItem::ByteArray(vec![12]),
+            RlpItem::ByteArray(vec![1]),
+            account(1).to_rlp_item(),
+            account(2).to_rlp_item(),
+            RlpItem::ByteArray(vec![1, 0]),
+            RlpItem::ByteArray(vec![20]),
+            RlpItem::ByteArray(vec![0]),
+            RlpItem::ByteArray(vec![1]),
+            RlpItem::ByteArray(vec![]),
+        ]);
+        assert_eq!(tx.to_rlp_item(), expect);
+    }
+
+    #[test]
+    fn spend_vector() {
+        // Not generated by the node: encoded with a separate Python RLP encoder following the
+        // spend template of `aec_spend_tx`, between the synthetic accounts 1 and 2, and signed for
+        // the network id "ae_mainnet".
+        let encoded = "tx_+JwLAfhCuEBesS9fQbZ4c/737tUnNFchxVG+hf4bD7PZsGOrQDw4WUkuAFzBs76LElvdScxNrFsWOZIfGxRkvOJPpHIb+g4KuFT4UgwBoQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAaEBAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgKCAQAUAAGFaGVsbG+Ey1pW";
+        let signed = SignedTx::from_encoded(&encoded.parse().unwrap()).unwrap();
+        assert_eq!(signed.to_encoded().to_string(), encoded);
+        assert_eq!(
+            hash_encoded(encoded).unwrap().to_string(),
+            "th_2jQvNnC9GQTUiNjE3fb1qR2hRvuauQrebnfK7rdUhvg5e4BD46"
+        );
+
+        let tx = Tx::deserialize_rlp(&signed.transaction).unwrap();
+        let expect = SpendTx {
+            sender_id: account(1),
+            recipient_id: account(2),
+            amount: 256,
+            fee: 20,
+            ttl: 0,
+            nonce: 1,
+            payload: b"hello".to_vec(),
+        };
+        assert_eq!(tx, Tx::from(expect));
+        assert_eq!(tx.serialize_rlp(), signed.transaction);
+
+        let kp = KeyPair::from_seed(&[1; 32]);
+        let res = signed.verify("ae_mainnet", &[kp.public_key()], SigningScheme::TxHash);
+        assert_eq!(res, Ok(()));
+    }
+
+    #[test]
+    fn wide_tags() {
+        let tx = &sample_txs()[4];
+        let item = tx.to_rlp_item();
+        assert_eq!(item.get::<Bytes>(0), Ok(vec![0x01, 0xf5]));
+    }
+
+    #[test]
+    fn invalid_tags() {
+        let unknown = RlpItem::List(vec![
+            RlpItem::ByteArray(vec![99]),
+            RlpItem::ByteArray(vec![1]),
+        ]);
+        assert_eq!(Tx::from_rlp_item(&unknown), Err(INVALID_TAG));
+        assert_eq!(Tx::deserialize_rlp_ref(&unknown.serialize()), Err(INVALID_TAG));
+        assert_eq!(Tx::from_rlp_item(&RlpItem::List(vec![])), Err(INVALID_TAG));
+
+        let mut claim = sample_txs()[1].to_rlp_item();
+        if let RlpItem::List(items) = &mut claim {
+            items[1] = RlpItem::ByteArray(vec![1]);
+        }
+        assert_eq!(
+            Tx::from_rlp_item(&claim),
+            Err(DecodingErr::InvalidVersion {
+                object: "NameClaimTx"
+            })
+        );
+    }
+
+    #[test]
+    fn signing_typed() {
+        let kp = KeyPair::from_seed(&[1; 32]);
+        let tx = &sample_txs()[0];
+        let signed = SignedTx::from_encoded(&sign(tx, NETWORK_ID, &kp)).unwrap();
+        assert_eq!(Tx::deserialize_rlp(&signed.transaction), Ok(tx.clone()));
+        let res = signed.verify(NETWORK_ID, &[kp.public_key()], SigningScheme::TxHash);
+        assert_eq!(res, Ok(()));
+    }
+
+    #[test]
+    fn tx_hashes() {
+        use blake2::{digest::consts::U32, Blake2b, Digest};
+
+        let kp = KeyPair::from_seed(&[1; 32]);
+        let encoded = sign(&sample_txs()[0], NETWORK_ID, &kp).to_string();
+        let signed: SignedTx = SignedTx::from_encoded(&encoded.parse().unwrap()).unwrap();
+
+        let expect: [u8; 32] = Blake2b::<U32>::digest(signed.serialize_rlp()).into();
+        assert_eq!(signed.hash().as_bytes(), &expect[..]);
+        assert!(signed.hash().to_string().starts_with("th_"));
+        assert_eq!(hash_encoded(&encoded), Ok(signed.hash()));
+
+        let unsigned = SignedTx::new(signed.transaction.clone());
+        assert_ne!(unsigned.hash(), signed.hash());
+    }
+
+    #[test]
+    fn hashing_unsigned() {
+        let tx = sample_txs()[0].to_encoded().to_string();
+        assert!(matches!(
+            hash_encoded(&tx),
+            Err(DecodingErr::InvalidObject {
+                object: "SignedTx",
+                ..
+            })
+        ));
+        let hash = TxHash::new(vec![1; 32]).unwrap().to_string();
+        assert_eq!(hash_encoded(&hash), Err(DecodingErr::InvalidPrefix));
+    }
+
+    #[test]
+    fn mismatched_signers() {
+        let kp1 = KeyPair::from_seed(&[1; 32]);
+        let kp2 = KeyPair::from_seed(&[2; 32]);
+        let mut signed = SignedTx::new(vec![3; 20]);
+        signed.sign(&kp1, NETWORK_ID);
+        signed.signatures.push(signed.signatures[0].clone());
+
+        let signers = [kp1.public_key(), kp2.public_key()];
+        let res = signed.verify(NETWORK_ID, &signers, SigningScheme::TxHash);
+        assert_eq!(res, Err(KeyErr::InvalidSignature));
+        let res = signed.verify(NETWORK_ID, &signers[..1], SigningScheme::TxHash);
+        assert_eq!(res, Err(KeyErr::InvalidSignature));
+    }
+
+    #[test]
+    fn duplicate_signatures() {
+        let kp = KeyPair::from_seed(&[1; 32]);
+        let mut signed = SignedTx::new(vec![3; 20]);
+        signed.sign(&kp, NETWORK_ID);
+        signed.sign(&kp, NETWORK_ID);
+        assert_eq!(signed.signatures.len(), 1);
+
+        signed.sign_with_scheme(&kp, NETWORK_ID, SigningScheme::TxBytes);
+        assert_eq!(signed.signatures.len(), 2);
+        assert!(signed.signatures[0] < signed.signatures[1]);
+    }
+}
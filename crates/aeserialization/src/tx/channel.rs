@@ -0,0 +1,186 @@
+use crate::id::Id;
+use crate::rlp::{FromRlpItem, FromRlpRef, ToRlpItem};
+use crate::Bytes;
+
+/// Identifier tag of a channel create transaction.
+pub const CHANNEL_CREATE_TX_TAG: u16 = 50;
+/// Identifier tag of a channel deposit transaction.
+pub const CHANNEL_DEPOSIT_TX_TAG: u16 = 51;
+/// Identifier tag of a channel withdraw transaction.
+pub const CHANNEL_WITHDRAW_TX_TAG: u16 = 52;
+/// Identifier tag of a channel mutual close transaction.
+pub const CHANNEL_CLOSE_MUTUAL_TX_TAG: u16 = 53;
+/// Identifier tag of a channel solo close transaction.
+pub const CHANNEL_CLOSE_SOLO_TX_TAG: u16 = 54;
+/// Identifier tag of a channel slash transaction.
+pub const CHANNEL_SLASH_TX_TAG: u16 = 55;
+/// Identifier tag of a channel settle transaction.
+pub const CHANNEL_SETTLE_TX_TAG: u16 = 56;
+/// Identifier tag of an off-chain channel transaction.
+pub const CHANNEL_OFFCHAIN_TX_TAG: u16 = 57;
+/// Identifier tag of a channel solo snapshot transaction.
+pub const CHANNEL_SNAPSHOT_SOLO_TX_TAG: u16 = 59;
+/// Identifier tag of a channel set delegates transaction.
+pub const CHANNEL_SET_DELEGATES_TX_TAG: u16 = 501;
+/// Identifier tag of a channel force progress transaction.
+pub const CHANNEL_FORCE_PROGRESS_TX_TAG: u16 = 521;
+
+/// Opens a state channel. See [crate::id::channel_id].
+#[derive(Debug, Clone, PartialEq, ToRlpItem, FromRlpItem, FromRlpRef)]
+#[rlp(tag = CHANNEL_CREATE_TX_TAG, vsn = 2u8)]
+pub struct ChannelCreateTx {
+    pub initiator_id: Id,
+    pub initiator_amount: u128,
+    pub responder_id: Id,
+    pub responder_amount: u128,
+    pub channel_reserve: u128,
+    pub lock_period: u64,
+    pub ttl: u64,
+    pub fee: u128,
+    pub initiator_delegate_ids: Vec<Id>,
+    pub responder_delegate_ids: Vec<Id>,
+    pub state_hash: Bytes,
+    pub nonce: u64,
+}
+
+/// Deposits tokens into a channel.
+#[derive(Debug, Clone, PartialEq, ToRlpItem, FromRlpItem, FromRlpRef)]
+#[rlp(tag = CHANNEL_DEPOSIT_TX_TAG, vsn = 1u8)]
+pub struct ChannelDepositTx {
+    pub channel_id: Id,
+    pub from_id: Id,
+    pub amount: u128,
+    pub ttl: u64,
+    pub fee: u128,
+    pub state_hash: Bytes,
+    pub round: u64,
+    pub nonce: u64,
+}
+
+/// Withdraws tokens from a channel.
+#[derive(Debug, Clone, PartialEq, ToRlpItem, FromRlpItem, FromRlpRef)]
+#[rlp(tag = CHANNEL_WITHDRAW_TX_TAG, vsn = 1u8)]
+pub struct ChannelWithdrawTx {
+    pub channel_id: Id,
+    pub to_id: Id,
+    pub amount: u128,
+    pub ttl: u64,
+    pub fee: u128,
+    pub state_hash: Bytes,
+    pub round: u64,
+    pub nonce: u64,
+}
+
+/// Closes a channel with the consent of both parties.
+#[derive(Debug, Clone, PartialEq, ToRlpItem, FromRlpItem, FromRlpRef)]
+#[rlp(tag = CHANNEL_CLOSE_MUTUAL_TX_TAG, vsn = 1u8)]
+pub struct ChannelCloseMutualTx {
+    pub channel_id: Id,
+    pub from_id: Id,
+    pub initiator_amount_final: u128,
+    pub responder_amount_final: u128,
+    pub ttl: u64,
+    pub fee: u128,
+    pub nonce: u64,
+}
+
+/// Starts closing a channel unilaterally, based on the latest off-chain state.
+#[derive(Debug, Clone, PartialEq, ToRlpItem, FromRlpItem, FromRlpRef)]
+#[rlp(tag = CHANNEL_CLOSE_SOLO_TX_TAG, vsn = 1u8)]
+pub struct ChannelCloseSoloTx {
+    pub channel_id: Id,
+    pub from_id: Id,
+    /// Serialized signed off-chain transaction.
+    pub payload: Bytes,
+    /// Serialized proof of inclusion of the channel participants.
+    pub poi: Bytes,
+    pub ttl: u64,
+    pub fee: u128,
+    pub nonce: u64,
+}
+
+/// Disputes a solo close with a more recent off-chain state.
+#[derive(Debug, Clone, PartialEq, ToRlpItem, FromRlpItem, FromRlpRef)]
+#[rlp(tag = CHANNEL_SLASH_TX_TAG, vsn = 1u8)]
+pub struct ChannelSlashTx {
+    pub channel_id: Id,
+    pub from_id: Id,
+    /// Serialized signed off-chain transaction.
+    pub payload: Bytes,
+    /// Serialized proof of inclusion of the channel participants.
+    pub poi: Bytes,
+    pub ttl: u64,
+    pub fee: u128,
+    pub nonce: u64,
+}
+
+/// Finalizes closing a channel after the lock period.
+#[derive(Debug, Clone, PartialEq, ToRlpItem, FromRlpItem, FromRlpRef)]
+#[rlp(tag = CHANNEL_SETTLE_TX_TAG, vsn = 1u8)]
+pub struct ChannelSettleTx {
+    pub channel_id: Id,
+    pub from_id: Id,
+    pub initiator_amount_final: u128,
+    pub responder_amount_final: u128,
+    pub ttl: u64,
+    pub fee: u128,
+    pub nonce: u64,
+}
+
+/// Off-chain update of a channel state, co-signed by the parties.
+#[derive(Debug, Clone, PartialEq, ToRlpItem, FromRlpItem, FromRlpRef)]
+#[rlp(tag = CHANNEL_OFFCHAIN_TX_TAG, vsn = 2u8)]
+pub struct ChannelOffchainTx {
+    pub channel_id: Id,
+    pub round: u64,
+    pub state_hash: Bytes,
+}
+
+/// Records the latest off-chain state on chain.
+#[derive(Debug, Clone, PartialEq, ToRlpItem, FromRlpItem, FromRlpRef)]
+#[rlp(tag = CHANNEL_SNAPSHOT_SOLO_TX_TAG, vsn = 1u8)]
+pub struct ChannelSnapshotSoloTx {
+    pub channel_id: Id,
+    pub from_id: Id,
+    /// Serialized signed off-chain transaction.
+    pub payload: Bytes,
+    pub ttl: u64,
+    pub fee: u128,
+    pub nonce: u64,
+}
+
+/// Replaces the delegates of the channel parties.
+#[derive(Debug, Clone, PartialEq, ToRlpItem, FromRlpItem, FromRlpRef)]
+#[rlp(tag = CHANNEL_SET_DELEGATES_TX_TAG, vsn = 1u8)]
+pub struct ChannelSetDelegatesTx {
+    pub channel_id: Id,
+    pub from_id: Id,
+    pub initiator_delegate_ids: Vec<Id>,
+    pub responder_delegate_ids: Vec<Id>,
+    pub state_hash: Bytes,
+    pub round: u64,
+    /// Serialized signed off-chain transaction.
+    pub payload: Bytes,
+    pub ttl: u64,
+    pub fee: u128,
+    pub nonce: u64,
+}
+
+/// Progresses the channel state on chain by applying a contract call.
+#[derive(Debug, Clone, PartialEq, ToRlpItem, FromRlpItem, FromRlpRef)]
+#[rlp(tag = CHANNEL_FORCE_PROGRESS_TX_TAG, vsn = 1u8)]
+pub struct ChannelForceProgressTx {
+    pub channel_id: Id,
+    pub from_id: Id,
+    /// Serialized signed off-chain transaction.
+    pub payload: Bytes,
+    pub round: u64,
+    /// Serialized off-chain update.
+    pub update: Bytes,
+    pub state_hash: Bytes,
+    /// Serialized off-chain state trees.
+    pub offchain_trees: Bytes,
+    pub ttl: u64,
+    pub fee: u128,
+    pub nonce: u64,
+}
@@ -0,0 +1,43 @@
+use crate::id::Id;
+use crate::rlp::{FromRlpItem, FromRlpRef, ToRlpItem};
+use crate::Bytes;
+
+/// Identifier tag of a contract create transaction.
+pub const CONTRACT_CREATE_TX_TAG: u16 = 42;
+/// Identifier tag of a contract call transaction.
+pub const CONTRACT_CALL_TX_TAG: u16 = 43;
+
+/// Deploys a contract. See [crate::id::contract_id].
+#[derive(Debug, Clone, PartialEq, ToRlpItem, FromRlpItem, FromRlpRef)]
+#[rlp(tag = CONTRACT_CREATE_TX_TAG, vsn = 1u8)]
+pub struct ContractCreateTx {
+    pub owner_id: Id,
+    pub nonce: u64,
+    /// Serialized [crate::contract_code::Code].
+    pub code: Bytes,
+    /// VM version in the upper 16 bits and ABI version in the lower 16 bits.
+    pub ct_version: u32,
+    pub fee: u128,
+    pub ttl: u64,
+    pub deposit: u128,
+    pub amount: u128,
+    pub gas: u64,
+    pub gas_price: u128,
+    pub call_data: Bytes,
+}
+
+/// Calls a contract.
+#[derive(Debug, Clone, PartialEq, ToRlpItem, FromRlpItem, FromRlpRef)]
+#[rlp(tag = CONTRACT_CALL_TX_TAG, vsn = 1u8)]
+pub struct ContractCallTx {
+    pub caller_id: Id,
+    pub nonce: u64,
+    pub contract_id: Id,
+    pub abi_version: u16,
+    pub fee: u128,
+    pub ttl: u64,
+    pub amount: u128,
+    pub gas: u64,
+    pub gas_price: u128,
+    pub call_data: Bytes,
+}
@@ -0,0 +1,41 @@
+use crate::id::Id;
+use crate::rlp::{FromRlpItem, FromRlpRef, ToRlpItem};
+use crate::Bytes;
+
+/// Identifier tag of a generalized account attach transaction.
+pub const GA_ATTACH_TX_TAG: u16 = 80;
+/// Identifier tag of a generalized account meta transaction.
+pub const GA_META_TX_TAG: u16 = 81;
+
+/// Turns an account into a generalized account, authenticated by a contract.
+#[derive(Debug, Clone, PartialEq, ToRlpItem, FromRlpItem, FromRlpRef)]
+#[rlp(tag = GA_ATTACH_TX_TAG, vsn = 1u8)]
+pub struct GaAttachTx {
+    pub owner_id: Id,
+    pub nonce: u64,
+    /// Serialized [crate::contract_code::Code].
+    pub code: Bytes,
+    /// Hash of the authentication function.
+    pub auth_fun: Bytes,
+    pub ct_version: u32,
+    pub fee: u128,
+    pub ttl: u64,
+    pub gas: u64,
+    pub gas_price: u128,
+    pub call_data: Bytes,
+}
+
+/// Wraps a transaction of a generalized account along with its authentication data.
+#[derive(Debug, Clone, PartialEq, ToRlpItem, FromRlpItem, FromRlpRef)]
+#[rlp(tag = GA_META_TX_TAG, vsn = 2u8)]
+pub struct GaMetaTx {
+    pub ga_id: Id,
+    /// Call data of the authentication function.
+    pub auth_data: Bytes,
+    pub abi_version: u16,
+    pub fee: u128,
+    pub gas: u64,
+    pub gas_price: u128,
+    /// Serialized inner signed transaction, with no signatures.
+    pub tx: Bytes,
+}
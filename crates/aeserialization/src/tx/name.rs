@@ -0,0 +1,83 @@
+use crate::id::Id;
+use crate::rlp::{FromRlpItem, FromRlpRef, ToRlpItem};
+use crate::Bytes;
+
+/// Identifier tag of a name claim transaction.
+pub const NAME_CLAIM_TX_TAG: u16 = 32;
+/// Identifier tag of a name preclaim transaction.
+pub const NAME_PRECLAIM_TX_TAG: u16 = 33;
+/// Identifier tag of a name update transaction.
+pub const NAME_UPDATE_TX_TAG: u16 = 34;
+/// Identifier tag of a name revoke transaction.
+pub const NAME_REVOKE_TX_TAG: u16 = 35;
+/// Identifier tag of a name transfer transaction.
+pub const NAME_TRANSFER_TX_TAG: u16 = 36;
+
+/// Commits to a name claim without revealing the name. See [crate::id::commitment_id].
+#[derive(Debug, Clone, PartialEq, ToRlpItem, FromRlpItem, FromRlpRef)]
+#[rlp(tag = NAME_PRECLAIM_TX_TAG, vsn = 1u8)]
+pub struct NamePreclaimTx {
+    pub account_id: Id,
+    pub nonce: u64,
+    pub commitment_id: Id,
+    pub fee: u128,
+    pub ttl: u64,
+}
+
+/// Claims a name, revealing the salt of the preceding commitment, or bids in its auction.
+#[derive(Debug, Clone, PartialEq, ToRlpItem, FromRlpItem, FromRlpRef)]
+#[rlp(tag = NAME_CLAIM_TX_TAG, vsn = 2u8)]
+pub struct NameClaimTx {
+    pub account_id: Id,
+    pub nonce: u64,
+    pub name: Bytes,
+    /// Zero for auction bids following the initial claim.
+    pub name_salt: num::BigUint,
+    pub name_fee: u128,
+    pub fee: u128,
+    pub ttl: u64,
+}
+
+/// Key of a name pointing to an id, eg. `account_pubkey`.
+#[derive(Debug, Clone, PartialEq, ToRlpItem, FromRlpItem, FromRlpRef)]
+pub struct NamePointer {
+    pub key: Bytes,
+    pub id: Id,
+}
+
+/// Updates the pointers and the expiration of a name.
+#[derive(Debug, Clone, PartialEq, ToRlpItem, FromRlpItem, FromRlpRef)]
+#[rlp(tag = NAME_UPDATE_TX_TAG, vsn = 1u8)]
+pub struct NameUpdateTx {
+    pub account_id: Id,
+    pub nonce: u64,
+    pub name_id: Id,
+    pub name_ttl: u64,
+    pub pointers: Vec<NamePointer>,
+    pub client_ttl: u64,
+    pub fee: u128,
+    pub ttl: u64,
+}
+
+/// Transfers a name to another account.
+#[derive(Debug, Clone, PartialEq, ToRlpItem, FromRlpItem, FromRlpRef)]
+#[rlp(tag = NAME_TRANSFER_TX_TAG, vsn = 1u8)]
+pub struct NameTransferTx {
+    pub account_id: Id,
+    pub nonce: u64,
+    pub name_id: Id,
+    pub recipient_id: Id,
+    pub fee: u128,
+    pub ttl: u64,
+}
+
+/// Gives up a name.
+#[derive(Debug, Clone, PartialEq, ToRlpItem, FromRlpItem, FromRlpRef)]
+#[rlp(tag = NAME_REVOKE_TX_TAG, vsn = 1u8)]
+pub struct NameRevokeTx {
+    pub account_id: Id,
+    pub nonce: u64,
+    pub name_id: Id,
+    pub fee: u128,
+    pub ttl: u64,
+}
@@ -0,0 +1,79 @@
+use crate::id::Id;
+use crate::rlp::{FromRlpItem, FromRlpRef, ToRlpItem};
+use crate::Bytes;
+
+/// Identifier tag of an oracle register transaction.
+pub const ORACLE_REGISTER_TX_TAG: u16 = 22;
+/// Identifier tag of an oracle query transaction.
+pub const ORACLE_QUERY_TX_TAG: u16 = 23;
+/// Identifier tag of an oracle response transaction.
+pub const ORACLE_RESPONSE_TX_TAG: u16 = 24;
+/// Identifier tag of an oracle extend transaction.
+pub const ORACLE_EXTEND_TX_TAG: u16 = 25;
+
+/// TTL type of a value relative to the current height.
+pub const TTL_DELTA: u8 = 0;
+/// TTL type of an absolute height.
+pub const TTL_BLOCK: u8 = 1;
+
+/// Registers the sending account as an oracle.
+#[derive(Debug, Clone, PartialEq, ToRlpItem, FromRlpItem, FromRlpRef)]
+#[rlp(tag = ORACLE_REGISTER_TX_TAG, vsn = 1u8)]
+pub struct OracleRegisterTx {
+    pub account_id: Id,
+    pub nonce: u64,
+    pub query_format: Bytes,
+    pub response_format: Bytes,
+    pub query_fee: u128,
+    /// See [TTL_DELTA] and [TTL_BLOCK].
+    pub oracle_ttl_type: u8,
+    pub oracle_ttl_value: u64,
+    pub fee: u128,
+    pub ttl: u64,
+    pub abi_version: u16,
+}
+
+/// Extends the lifetime of an oracle.
+#[derive(Debug, Clone, PartialEq, ToRlpItem, FromRlpItem, FromRlpRef)]
+#[rlp(tag = ORACLE_EXTEND_TX_TAG, vsn = 1u8)]
+pub struct OracleExtendTx {
+    pub oracle_id: Id,
+    pub nonce: u64,
+    /// Only [TTL_DELTA] is allowed.
+    pub oracle_ttl_type: u8,
+    pub oracle_ttl_value: u64,
+    pub fee: u128,
+    pub ttl: u64,
+}
+
+/// Posts a query to an oracle. See [crate::id::oracle_query_id].
+#[derive(Debug, Clone, PartialEq, ToRlpItem, FromRlpItem, FromRlpRef)]
+#[rlp(tag = ORACLE_QUERY_TX_TAG, vsn = 1u8)]
+pub struct OracleQueryTx {
+    pub sender_id: Id,
+    pub nonce: u64,
+    pub oracle_id: Id,
+    pub query: Bytes,
+    pub query_fee: u128,
+    pub query_ttl_type: u8,
+    pub query_ttl_value: u64,
+    /// Only [TTL_DELTA] is allowed.
+    pub response_ttl_type: u8,
+    pub response_ttl_value: u64,
+    pub fee: u128,
+    pub ttl: u64,
+}
+
+/// Responds to an oracle query.
+#[derive(Debug, Clone, PartialEq, ToRlpItem, FromRlpItem, FromRlpRef)]
+#[rlp(tag = ORACLE_RESPONSE_TX_TAG, vsn = 1u8)]
+pub struct OracleResponseTx {
+    pub oracle_id: Id,
+    pub nonce: u64,
+    pub query_id: Bytes,
+    pub response: Bytes,
+    pub response_ttl_type: u8,
+    pub response_ttl_value: u64,
+    pub fee: u128,
+    pub ttl: u64,
+}
@@ -0,0 +1,33 @@
+use crate::id::Id;
+use crate::rlp::{FromRlpItem, FromRlpRef, ToRlpItem};
+use crate::Bytes;
+
+/// Identifier tag of a spend transaction.
+pub const SPEND_TX_TAG: u16 = 12;
+/// Identifier tag of a paying-for transaction.
+pub const PAYING_FOR_TX_TAG: u16 = 82;
+
+/// Transfers tokens between accounts.
+#[derive(Debug, Clone, PartialEq, ToRlpItem, FromRlpItem, FromRlpRef)]
+#[rlp(tag = SPEND_TX_TAG, vsn = 1u8)]
+pub struct SpendTx {
+    pub sender_id: Id,
+    /// Account, or a name resolving to one.
+    pub recipient_id: Id,
+    pub amount: u128,
+    pub fee: u128,
+    pub ttl: u64,
+    pub nonce: u64,
+    pub payload: Bytes,
+}
+
+/// Pays the fees of an inner transaction on behalf of its signer.
+#[derive(Debug, Clone, PartialEq, ToRlpItem, FromRlpItem, FromRlpRef)]
+#[rlp(tag = PAYING_FOR_TX_TAG, vsn = 1u8)]
+pub struct PayingForTx {
+    pub payer_id: Id,
+    pub nonce: u64,
+    pub fee: u128,
+    /// Serialized signed inner transaction.
+    pub tx: Bytes,
+}